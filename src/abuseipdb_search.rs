@@ -0,0 +1,294 @@
+use reqwest::Client;
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::io::Write;
+use std::net::IpAddr;
+use serde_json::{json, Value};
+use crate::model::{value_to_string, IndicatorReport};
+use crate::options::SearchOptions;
+use crate::error::SearchError;
+use crate::read_list::open_output_file;
+
+// Recent reports kept per IP when no --limit is given
+const ABUSEIPDB_DEFAULT_REPORTS: usize = 10;
+
+pub fn is_ip(target: &str) -> bool {
+    target.parse::<IpAddr>().is_ok()
+}
+
+pub fn is_cidr(target: &str) -> bool {
+    match target.split_once('/') {
+        Some((network, prefix)) => network.parse::<IpAddr>().is_ok() && prefix.parse::<u8>().is_ok(),
+        None => false,
+    }
+}
+
+pub fn get_abuseipdb_api_key() -> Option<String> {
+    env::var("ABUSEIPDB_API").ok()
+}
+
+// https://www.abuseipdb.com/categories
+pub fn abuseipdb_category_name(id: u64) -> &'static str {
+    match id {
+        1 => "DNS Compromise",
+        2 => "DNS Poisoning",
+        3 => "Fraud Orders",
+        4 => "DDoS Attack",
+        5 => "FTP Brute-Force",
+        6 => "Ping of Death",
+        7 => "Phishing",
+        8 => "Fraud VoIP",
+        9 => "Open Proxy",
+        10 => "Web Spam",
+        11 => "Email Spam",
+        12 => "Blog Spam",
+        13 => "VPN IP",
+        14 => "Port Scan",
+        15 => "Hacking",
+        16 => "SQL Injection",
+        17 => "Spoofing",
+        18 => "Brute-Force",
+        19 => "Bad Web Bot",
+        20 => "Exploited Host",
+        21 => "Web App Attack",
+        22 => "SSH",
+        23 => "IoT Targeted",
+        _ => "Unknown",
+    }
+}
+
+pub async fn query_abuseipdb(endpoint: &str, params: &[(&str, String)]) -> Result<Value, Box<dyn Error>> {
+    let api_key = get_abuseipdb_api_key().expect("ABUSEIPDB_API not found");
+
+    let url = format!("https://api.abuseipdb.com/api/v2/{}", endpoint);
+
+    let client = Client::new();
+
+    let response = client
+        .get(&url)
+        .query(params)
+        .header("Key", api_key)
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .map_err(|err| SearchError::new(err.to_string()))?;
+
+    let response_body = response
+        .text()
+        .await
+        .map_err(|err| SearchError::new(err.to_string()))?;
+
+    let parsed_result: Value = serde_json::from_str(&response_body)?;
+
+    // Errors come back as {"errors": [{"detail": "...", "status": 422}]}
+    if let Some(error) = parsed_result["errors"].as_array().and_then(|errors| errors.first()) {
+        return Err(Box::new(SearchError::new(
+            error["detail"].as_str().unwrap_or("AbuseIPDB error").to_string(),
+        )));
+    }
+
+    Ok(parsed_result)
+}
+
+pub async fn query_abuseipdb_check(ip: &str, max_age_days: u32) -> Result<Value, Box<dyn Error>> {
+    query_abuseipdb(
+        "check",
+        &[
+            ("ipAddress", ip.to_string()),
+            ("maxAgeInDays", max_age_days.to_string()),
+            ("verbose", "true".to_string()),
+        ],
+    )
+    .await
+}
+
+pub async fn query_abuseipdb_check_block(network: &str, max_age_days: u32) -> Result<Value, Box<dyn Error>> {
+    query_abuseipdb(
+        "check-block",
+        &[("network", network.to_string()), ("maxAgeInDays", max_age_days.to_string())],
+    )
+    .await
+}
+
+fn category_names(categories: &Value) -> Vec<String> {
+    categories
+        .as_array()
+        .map(|ids| {
+            ids.iter()
+                .filter_map(|id| id.as_u64())
+                .map(|id| abuseipdb_category_name(id).to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Score, ISP and usage type, category counts across all reports and the most recent comments
+pub fn parse_abuseipdb_check(ip: &str, result: &Value, report_limit: usize) -> IndicatorReport {
+    let data = &result["data"];
+    let mut report = IndicatorReport::new("AbuseIPDB", ip, "ip");
+
+    if let Some(score) = data["abuseConfidenceScore"].as_u64() {
+        report.details.insert("abuse_confidence_score".to_string(), Value::from(score));
+    }
+    for (key, name) in [
+        ("isp", "isp"),
+        ("usageType", "usage_type"),
+        ("domain", "domain"),
+        ("countryCode", "country"),
+        ("totalReports", "total_reports"),
+        ("numDistinctUsers", "distinct_reporters"),
+        ("lastReportedAt", "last_reported_at"),
+    ] {
+        if !data[key].is_null() {
+            report.details.insert(name.to_string(), data[key].clone());
+        }
+    }
+
+    let reports = data["reports"].as_array().cloned().unwrap_or_default();
+
+    let mut category_counts: BTreeMap<String, u64> = BTreeMap::new();
+    for entry in &reports {
+        for name in category_names(&entry["categories"]) {
+            *category_counts.entry(name).or_insert(0) += 1;
+        }
+    }
+    if !category_counts.is_empty() {
+        report.details.insert("report_categories".to_string(), json!(category_counts));
+    }
+
+    // Reports are returned newest first
+    let recent: Vec<Value> = reports
+        .iter()
+        .take(report_limit)
+        .map(|entry| {
+            json!({
+                "reported_at": entry["reportedAt"],
+                "categories": category_names(&entry["categories"]),
+                "comment": value_to_string(&entry["comment"]),
+                "reporter_country": entry["reporterCountryCode"],
+            })
+        })
+        .collect();
+    if !recent.is_empty() {
+        report.details.insert("recent_reports".to_string(), Value::Array(recent));
+    }
+
+    report
+}
+
+// The shared summary plus the abuse confidence score
+pub fn abuseipdb_summary_line(report: &IndicatorReport) -> String {
+    let mut line = report.summary_line();
+    if let Some(score) = report.details.get("abuse_confidence_score").and_then(Value::as_u64) {
+        line.push_str(&format!("  abuse={}%", score));
+    }
+    line
+}
+
+// One line for the block, then one per reported address, highest confidence first
+pub fn abuseipdb_block_summary(network: &str, block: &Value) -> String {
+    let data = &block["data"];
+    let mut reported: Vec<&Value> = data["reportedAddress"].as_array().into_iter().flatten().collect();
+    reported.sort_by_key(|entry| std::cmp::Reverse(entry["abuseConfidenceScore"].as_u64().unwrap_or(0)));
+
+    let mut lines = vec![format!(
+        "{} (cidr)  reported={}/{}",
+        network,
+        reported.len(),
+        value_to_string(&data["numPossibleHosts"]).unwrap_or_else(|| "?".to_string())
+    )];
+    for entry in reported {
+        lines.push(format!(
+            "{}  abuse={}%  reports={}  last={}",
+            entry["ipAddress"].as_str().unwrap_or("-"),
+            entry["abuseConfidenceScore"].as_u64().unwrap_or(0),
+            entry["numReports"].as_u64().unwrap_or(0),
+            entry["mostRecentReport"].as_str().unwrap_or("-"),
+        ));
+    }
+
+    lines.join("\n")
+}
+
+pub async fn run_single_search_abuseipdb(
+    target: &str,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn Error>> {
+    let abuseipdb_result = if is_ip(target) {
+        let check = query_abuseipdb_check(target, options.max_age_days).await?;
+        let report_limit = options.limit.unwrap_or(ABUSEIPDB_DEFAULT_REPORTS);
+        let report = parse_abuseipdb_check(target, &check, report_limit);
+        if options.summary {
+            abuseipdb_summary_line(&report)
+        } else {
+            serde_json::to_string_pretty(&report)?
+        }
+    } else if is_cidr(target) {
+        let block = query_abuseipdb_check_block(target, options.max_age_days).await?;
+        if options.summary {
+            abuseipdb_block_summary(target, &block)
+        } else {
+            serde_json::to_string_pretty(&block["data"])?
+        }
+    } else {
+        println!("Invalid target: {}", target);
+        return Ok(());
+    };
+
+    match output_file {
+        Some(file_path) => {
+            let mut file = open_output_file(file_path)?;
+            writeln!(file, "AbuseIPDB:\n{}", abuseipdb_result)?;
+        }
+        None => {
+            println!("AbuseIPDB:");
+            println!("{}", abuseipdb_result);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_categories_are_decoded_and_counted() {
+        let result = json!({"data": {
+            "abuseConfidenceScore": 87,
+            "isp": "Example ISP",
+            "reports": [
+                {"reportedAt": "2024-01-02", "categories": [18, 22], "comment": "ssh brute force"},
+                {"reportedAt": "2024-01-01", "categories": [14, 22, 99], "comment": ""}
+            ]
+        }});
+        let report = parse_abuseipdb_check("192.0.2.1", &result, 1);
+
+        assert_eq!(
+            report.details["report_categories"],
+            json!({"Brute-Force": 1, "Port Scan": 1, "SSH": 2, "Unknown": 1})
+        );
+        assert_eq!(report.details["recent_reports"].as_array().unwrap().len(), 1);
+        assert_eq!(report.details["recent_reports"][0]["categories"], json!(["Brute-Force", "SSH"]));
+        assert_eq!(abuseipdb_summary_line(&report), "192.0.2.1 (ip)  abuse=87%");
+    }
+
+    #[test]
+    fn block_summary_lists_reported_addresses_by_score() {
+        let block = json!({"data": {
+            "numPossibleHosts": 256,
+            "reportedAddress": [
+                {"ipAddress": "192.0.2.7", "numReports": 3, "mostRecentReport": "2024-01-01", "abuseConfidenceScore": 20},
+                {"ipAddress": "192.0.2.9", "numReports": 40, "mostRecentReport": "2024-01-03", "abuseConfidenceScore": 100}
+            ]
+        }});
+        assert_eq!(
+            abuseipdb_block_summary("192.0.2.0/24", &block),
+            "192.0.2.0/24 (cidr)  reported=2/256\n\
+             192.0.2.9  abuse=100%  reports=40  last=2024-01-03\n\
+             192.0.2.7  abuse=20%  reports=3  last=2024-01-01"
+        );
+    }
+}
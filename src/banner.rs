@@ -1,5 +1,4 @@
 use colored::*;
-use term_size;
 
 pub fn display_banner() {
     let banner_text = r#"
@@ -21,7 +20,7 @@ pub fn display_banner() {
         let banner_lines: Vec<&str> = banner_text.lines().collect();
 
         for line in banner_lines {
-            let padding = (w as usize - line.chars().count()) / 2;
+            let padding = (w - line.chars().count()) / 2;
             let padded_line: String = " ".repeat(padding) + line;
             println!("{}", padded_line.green());
         }
@@ -0,0 +1,303 @@
+use std::env;
+use std::error::Error;
+use std::io::Write;
+use std::net::IpAddr;
+use serde_json::Value;
+use crate::cve_feed::sort_vulnerabilities;
+use crate::model::{value_to_string, HostRecord, RiskVerdict, Service, SubdomainRecord, Vulnerability};
+use crate::options::SearchOptions;
+use crate::error::SearchError;
+use crate::target::is_domain;
+use crate::read_list::open_output_file;
+
+// The subdomain endpoint returns 100 names per page, and every page costs a query credit
+const BINARYEDGE_PAGE_SIZE: usize = 100;
+
+pub fn is_ip(target: &str) -> bool {
+    target.parse::<IpAddr>().is_ok()
+}
+
+pub fn get_binaryedge_api_key() -> Option<String> {
+    env::var("BINARYEDGE_API").ok()
+}
+
+pub async fn query_binaryedge(path: &str, params: &[(&str, String)]) -> Result<Value, Box<dyn Error>> {
+    let binaryedge_api_key = get_binaryedge_api_key().expect("BINARYEDGE_API not found");
+
+    let url = format!("https://api.binaryedge.io/v2/query/{}", path);
+
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(&url)
+        .query(params)
+        .header("X-Key", binaryedge_api_key)
+        .send()
+        .await
+        .map_err(|err| SearchError::new(err.to_string()))?;
+
+    let response_body = response
+        .text()
+        .await
+        .map_err(|err| SearchError::new(err.to_string()))?;
+
+    let parsed_result: Value = serde_json::from_str(&response_body)?;
+
+    // Errors come back as {"status": 400, "title": "...", "message": "..."}
+    if parsed_result["status"].as_u64().is_some_and(|status| status >= 400) {
+        return Err(Box::new(SearchError::new(format!(
+            "BinaryEdge: {}",
+            parsed_result["message"].as_str().or(parsed_result["title"].as_str()).unwrap_or("request failed")
+        ))));
+    }
+
+    Ok(parsed_result)
+}
+
+// Each event is one port, with a result per scan module (service, ssl, http, ...)
+pub fn parse_binaryedge_ip(ip: &str, data: &Value) -> HostRecord {
+    let mut record = HostRecord::new("BinaryEdge", ip);
+
+    for event in data["events"].as_array().into_iter().flatten() {
+        for result in event["results"].as_array().into_iter().flatten() {
+            let port = result["target"]["port"].as_u64().or(event["port"].as_u64());
+            let port = match port.and_then(|port| u16::try_from(port).ok()) {
+                Some(port) => port,
+                None => continue,
+            };
+            let service_data = &result["result"]["data"]["service"];
+            let service = Service {
+                port,
+                protocol: value_to_string(&result["target"]["protocol"]),
+                product: value_to_string(&service_data["product"]).or_else(|| value_to_string(&service_data["name"])),
+                version: value_to_string(&service_data["version"]),
+            };
+
+            // Several modules report the same port; keep the one that names the service
+            match record.services.iter_mut().find(|s| s.port == port && s.protocol == service.protocol) {
+                Some(existing) if existing.product.is_none() => *existing = service,
+                Some(_) => {}
+                None => record.services.push(service),
+            }
+        }
+    }
+
+    record.services.sort_by_key(|service| service.port);
+    record
+}
+
+// CVEs are grouped by the CPE they were matched against
+pub fn parse_binaryedge_cves(data: &Value) -> Vec<Vulnerability> {
+    let mut vulnerabilities: Vec<Vulnerability> = Vec::new();
+    let groups = data["results"].as_array().or(data["cves"].as_array());
+
+    for group in groups.into_iter().flatten() {
+        for cve in group["cves"].as_array().into_iter().flatten() {
+            let id = match cve["cve"].as_str() {
+                Some(id) => id.to_string(),
+                None => continue,
+            };
+            if vulnerabilities.iter().any(|v| v.id == id) {
+                continue;
+            }
+            vulnerabilities.push(Vulnerability {
+                id,
+                cvss: cve["cvss"].as_f64(),
+                cpe: value_to_string(&group["cpe"]),
+                ..Default::default()
+            });
+        }
+    }
+
+    vulnerabilities
+}
+
+// The normalized 0-100 score becomes the verdict, and each non-zero part of it a flag
+pub fn parse_binaryedge_score(data: &Value) -> Option<RiskVerdict> {
+    let score = data["normalized_ip_score"].as_u64()?;
+
+    let flags = data["normalized_ip_score_detailed"]
+        .as_object()
+        .map(|parts| {
+            parts
+                .iter()
+                .filter(|(_, value)| value.as_u64().unwrap_or(0) > 0)
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(RiskVerdict {
+        verdict: format!("score {}/100", score),
+        flags,
+        ..Default::default()
+    })
+}
+
+// The host lookup is required; the CVE and score endpoints are best-effort, since not
+// every plan includes them and they fail once credits run out
+pub async fn search_binaryedge_ip(ip: &str, options: &SearchOptions) -> Result<HostRecord, Box<dyn Error>> {
+    let host = query_binaryedge(&format!("ip/{}", ip), &[]).await?;
+    let mut record = parse_binaryedge_ip(ip, &host);
+
+    match query_binaryedge(&format!("cve/ip/{}", ip), &[]).await {
+        Ok(cves) => record.vulnerabilities = filter_binaryedge_cves(parse_binaryedge_cves(&cves), options),
+        Err(err) => println!("BinaryEdge CVEs for {} unavailable: {}", ip, err),
+    }
+
+    match query_binaryedge(&format!("score/ip/{}", ip), &[]).await {
+        Ok(score) => record.risk = parse_binaryedge_score(&score),
+        Err(err) => println!("BinaryEdge score for {} unavailable: {}", ip, err),
+    }
+
+    Ok(record)
+}
+
+// Enriched from the local CVE feed when one is loaded, otherwise BinaryEdge's own scores
+fn filter_binaryedge_cves(vulnerabilities: Vec<Vulnerability>, options: &SearchOptions) -> Vec<Vulnerability> {
+    match &options.cve_feed {
+        Some(feed) => {
+            let ids: Vec<String> = vulnerabilities.iter().map(|v| v.id.clone()).collect();
+            let mut enriched = feed.enrich(&ids, options.min_cvss);
+            // The feed knows nothing about the CPE BinaryEdge matched, so carry it over
+            for vuln in &mut enriched {
+                vuln.cpe = vulnerabilities.iter().find(|v| v.id == vuln.id).and_then(|v| v.cpe.clone());
+            }
+            enriched
+        }
+        None => {
+            let mut vulnerabilities: Vec<Vulnerability> = vulnerabilities
+                .into_iter()
+                .filter(|v| options.min_cvss.is_none_or(|min| v.cvss.is_some_and(|score| score >= min)))
+                .collect();
+            sort_vulnerabilities(&mut vulnerabilities);
+            vulnerabilities
+        }
+    }
+}
+
+// Pages through the subdomain list up to --limit names; one page without a limit
+pub async fn query_binaryedge_subdomains(domain: &str, limit: Option<usize>) -> Result<SubdomainRecord, Box<dyn Error>> {
+    let wanted = limit.unwrap_or(BINARYEDGE_PAGE_SIZE).max(1);
+    let mut names: Vec<String> = Vec::new();
+    let mut page = 1;
+
+    loop {
+        let page_result = query_binaryedge(
+            &format!("domains/subdomain/{}", domain),
+            &[("page", page.to_string())],
+        )
+        .await?;
+
+        let page_names: Vec<String> = page_result["events"]
+            .as_array()
+            .map(|events| events.iter().filter_map(value_to_string).collect())
+            .unwrap_or_default();
+        let total = page_result["total"].as_u64().unwrap_or(0) as usize;
+        let page_was_empty = page_names.is_empty();
+        names.extend(page_names);
+
+        if page_was_empty || names.len() >= wanted || names.len() >= total {
+            break;
+        }
+        page += 1;
+    }
+    names.truncate(wanted);
+
+    Ok(SubdomainRecord::new("BinaryEdge", domain, names))
+}
+
+pub async fn run_single_search_binaryedge(
+    target: &str,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let result = if is_ip(target) {
+        let record = search_binaryedge_ip(target, options).await?;
+        if options.summary {
+            record.summary_line()
+        } else {
+            serde_json::to_string_pretty(&record)?
+        }
+    } else if is_domain(target) {
+        let record = query_binaryedge_subdomains(target, options.limit).await?;
+        serde_json::to_string_pretty(&record)?
+    } else {
+        println!("Invalid target: {}", target);
+        return Ok(());
+    };
+
+    match output_file {
+        Some(file_path) => {
+            let mut file = open_output_file(file_path)?;
+            writeln!(file, "BinaryEdge:\n{}", result)?;
+        }
+        None => {
+            println!("BinaryEdge:");
+            println!("{}", result);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn host_events_become_services_preferring_named_ones() {
+        let data = json!({
+            "events": [
+                {"port": 443, "results": [
+                    {"target": {"port": 443, "protocol": "tcp"}, "result": {"data": {"cert_info": {}}}},
+                    {"target": {"port": 443, "protocol": "tcp"}, "result": {"data": {"service": {"name": "https", "product": "nginx", "version": "1.18.0"}}}}
+                ]},
+                {"port": 22, "results": [
+                    {"target": {"port": 22, "protocol": "tcp"}, "result": {"data": {"service": {"name": "ssh"}}}},
+                    {"target": {"port": 22, "protocol": "tcp"}, "result": {"data": {"service": {"product": "OpenSSH"}}}}
+                ]},
+                {"port": 53, "results": [{"target": {"protocol": "udp"}, "result": {}}]},
+                {"port": 70000, "results": [{"target": {"port": 70000, "protocol": "tcp"}, "result": {}}]}
+            ]
+        });
+        let record = parse_binaryedge_ip("192.0.2.1", &data);
+
+        let services: Vec<(u16, Option<&str>, Option<&str>)> = record
+            .services
+            .iter()
+            .map(|s| (s.port, s.protocol.as_deref(), s.product.as_deref()))
+            .collect();
+        assert_eq!(
+            services,
+            [(22, Some("tcp"), Some("ssh")), (53, Some("udp"), None), (443, Some("tcp"), Some("nginx"))]
+        );
+        assert_eq!(record.services[2].version.as_deref(), Some("1.18.0"));
+    }
+
+    #[test]
+    fn cves_are_deduplicated_across_cpes() {
+        let data = json!({"results": [
+            {"cpe": "cpe:/a:openbsd:openssh:7.4", "cves": [{"cve": "CVE-2018-15473", "cvss": 5.0}]},
+            {"cpe": "cpe:/a:nginx:nginx:1.18.0", "cves": [{"cve": "CVE-2021-23017", "cvss": 7.5}, {"cve": "CVE-2018-15473", "cvss": 5.0}]}
+        ]});
+        let cves = parse_binaryedge_cves(&data);
+        let ids: Vec<&str> = cves.iter().map(|v| v.id.as_str()).collect();
+        assert_eq!(ids, ["CVE-2018-15473", "CVE-2021-23017"]);
+        assert_eq!(cves[1].cvss, Some(7.5));
+        assert_eq!(cves[1].cpe.as_deref(), Some("cpe:/a:nginx:nginx:1.18.0"));
+        assert_eq!(cves[1].summary, None);
+    }
+
+    #[test]
+    fn score_keeps_non_zero_parts_as_flags() {
+        let data = json!({
+            "normalized_ip_score": 42,
+            "normalized_ip_score_detailed": {"cve": 60, "attack_surface": 20, "encryption": 0}
+        });
+        let risk = parse_binaryedge_score(&data).unwrap();
+        assert_eq!(risk.verdict, "score 42/100");
+        assert_eq!(risk.flags, ["attack_surface=20", "cve=60"]);
+        assert!(parse_binaryedge_score(&json!({})).is_none());
+    }
+}
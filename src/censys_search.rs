@@ -3,11 +3,16 @@ use std::env;
 use std::error::Error;
 use std::fmt;
 use std::net::IpAddr;
-use base64;
-use std::fs::File;
 use std::io::Write;
 use serde_json::Value;
 use regex::Regex;
+use serde_json::json;
+use crate::options::SearchOptions;
+use crate::target::Network;
+use crate::read_list::open_output_file;
+
+// Largest per_page the hosts search accepts
+const CENSYS_PAGE_SIZE: usize = 100;
 
 #[derive(Debug)]
 pub struct CensysSearchError {
@@ -88,8 +93,8 @@ pub async fn run_single_search_censys(
 
         match output_file {
             Some(file_path) => {
-                let mut file = File::create(file_path)?;
-                write!(file, "{}", serde_json::to_string_pretty(&parsed_result)?)?;
+                let mut file = open_output_file(file_path)?;
+                writeln!(file, "Censys:\n{}", serde_json::to_string_pretty(&parsed_result)?)?;
             }
             None => {
                 println!("Censys:");
@@ -102,4 +107,90 @@ pub async fn run_single_search_censys(
     Ok(())
 }
 
+pub fn censys_network_query(network: &Network) -> String {
+    match network {
+        Network::Asn(asn) => format!("autonomous_system.asn: {}", asn),
+        Network::Org(org) => format!("autonomous_system.name: \"{}\"", org.replace('"', "")),
+    }
+}
+
+pub async fn query_censys_search(
+    query: &str,
+    per_page: usize,
+    cursor: Option<&str>,
+) -> Result<Value, Box<dyn Error>> {
+    let censys_id = get_censys_id().expect("CENSYS_ID not found");
+    let censys_secret = get_censys_secret().expect("CENSYS_SECRET not found");
+
+    let client = reqwest::Client::new();
+
+    let mut params = vec![("q", query.to_string()), ("per_page", per_page.to_string())];
+    if let Some(cursor) = cursor {
+        params.push(("cursor", cursor.to_string()));
+    }
+
+    let response = client
+        .get("https://search.censys.io/api/v2/hosts/search")
+        .query(&params)
+        .basic_auth(censys_id, Some(censys_secret))
+        .send()
+        .await
+        .map_err(|err| CensysSearchError::new(err.to_string()))?;
+
+    let response_body = response
+        .text()
+        .await
+        .map_err(|err| CensysSearchError::new(err.to_string()))?;
+
+    Ok(serde_json::from_str(&response_body)?)
+}
+
+// Follows result.links.next up to --limit hosts; a single page without a limit
+pub async fn run_network_search_censys(
+    network: &Network,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let query = censys_network_query(network);
+    let wanted = options.limit.unwrap_or(CENSYS_PAGE_SIZE).max(1);
+    let mut hits: Vec<Value> = Vec::new();
+    let mut total = Value::Null;
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let per_page = CENSYS_PAGE_SIZE.min(wanted.saturating_sub(hits.len()));
+        let page = query_censys_search(&query, per_page, cursor.as_deref()).await?;
+        if let Some(error) = page["error"].as_str() {
+            return Err(Box::new(CensysSearchError::new(error.to_string())));
+        }
 
+        let page_hits = page["result"]["hits"].as_array().cloned().unwrap_or_default();
+        total = page["result"]["total"].clone();
+        let page_was_empty = page_hits.is_empty();
+        hits.extend(page_hits);
+
+        cursor = page["result"]["links"]["next"].as_str().filter(|c| !c.is_empty()).map(String::from);
+        if page_was_empty || cursor.is_none() || hits.len() >= wanted {
+            break;
+        }
+    }
+    hits.truncate(wanted);
+
+    let parsed_result = json!({
+        "query": query,
+        "total": total,
+        "hits": hits,
+    });
+
+    match output_file {
+        Some(file_path) => {
+            let mut file = open_output_file(file_path)?;
+            writeln!(file, "Censys:\n{}", serde_json::to_string_pretty(&parsed_result)?)?;
+        }
+        None => {
+            println!("Censys:");
+            println!("{}", serde_json::to_string_pretty(&parsed_result)?);
+        }
+    }
+    Ok(())
+}
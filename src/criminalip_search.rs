@@ -1,12 +1,13 @@
-use reqwest;
 use std::env;
 use std::error::Error;
 use std::fmt;
 use std::net::IpAddr;
 use regex::Regex;
 use serde_json::Value;
-use std::fs::File;
 use std::io::Write;
+use crate::model::{value_to_string, HostRecord, RiskVerdict, Service, Vulnerability};
+use crate::options::SearchOptions;
+use crate::read_list::open_output_file;
 
 #[derive(Debug)]
 pub struct CriminalIpSearchError {
@@ -100,28 +101,118 @@ pub async fn query_criminalip_ip_data(target: &str) -> Result<String, Box<dyn Er
     Ok(response_body)
 }
 
-// Updated run_single_search_criminalip function
+// CriminalIP scores from best to worst, used to pick the overall verdict
+const SCORE_LEVELS: [&str; 5] = ["safe", "low", "moderate", "dangerous", "critical"];
+
+// None for missing or unrecognised levels, so they never outrank a real score
+fn score_rank(score: Option<&str>) -> Option<usize> {
+    score.and_then(|s| SCORE_LEVELS.iter().position(|level| level.eq_ignore_ascii_case(s)))
+}
+
+pub fn parse_criminalip_ip_data(ip: &str, data: &Value) -> HostRecord {
+    let mut record = HostRecord::new("CriminalIP", ip);
+
+    let inbound = value_to_string(&data["score"]["inbound"]);
+    let outbound = value_to_string(&data["score"]["outbound"]);
+
+    let verdict = match (score_rank(inbound.as_deref()), score_rank(outbound.as_deref())) {
+        (None, None) => None,
+        (inbound_rank, outbound_rank) if inbound_rank >= outbound_rank => inbound.clone(),
+        _ => outbound.clone(),
+    };
+
+    // Only the is_* issues that are set, e.g. "is_vpn": true becomes "vpn"
+    let flags = data["issues"]
+        .as_object()
+        .map(|issues| {
+            issues
+                .iter()
+                .filter(|(_, set)| set.as_bool().unwrap_or(false))
+                .map(|(name, _)| name.trim_start_matches("is_").to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    record.risk = Some(RiskVerdict {
+        verdict: verdict.unwrap_or_else(|| "Unknown".to_string()),
+        inbound,
+        outbound,
+        flags,
+    });
+
+    if let Some(ports) = data["port"]["data"].as_array() {
+        for port in ports {
+            let port_no = match port["open_port_no"].as_u64().and_then(|p| u16::try_from(p).ok()) {
+                Some(p) => p,
+                None => continue,
+            };
+            let service = Service {
+                port: port_no,
+                protocol: value_to_string(&port["socket"]),
+                product: value_to_string(&port["app_name"]),
+                version: value_to_string(&port["app_version"]),
+            };
+            if !record.services.contains(&service) {
+                record.services.push(service);
+            }
+        }
+    }
+
+    if let Some(vulns) = data["vulnerability"]["data"].as_array() {
+        for vuln in vulns {
+            let id = match vuln["cve_id"].as_str() {
+                Some(id) => id.to_string(),
+                None => continue,
+            };
+            if record.vulnerabilities.iter().any(|v| v.id == id) {
+                continue;
+            }
+            // Older CVEs only carry a v2 score, reported as 0 for v3
+            let cvss = vuln["cvssv3_score"]
+                .as_f64()
+                .filter(|score| *score > 0.0)
+                .or_else(|| vuln["cvssv2_score"].as_f64().filter(|score| *score > 0.0));
+            record.vulnerabilities.push(Vulnerability {
+                id,
+                cvss,
+                summary: value_to_string(&vuln["cve_description"]),
+                ..Default::default()
+            });
+        }
+    }
+
+    record
+}
+
 pub async fn run_single_search_criminalip(
     target: &str,
     output_file: Option<&str>,
+    options: &SearchOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
     if is_ip(target) || is_domain(target) {
-        let criminalip_result = if is_domain(target) {
-            query_criminalip_domain_scan(target).await?
+        let result = if is_domain(target) {
+            let criminalip_result = query_criminalip_domain_scan(target).await?;
+            let parsed_result = serde_json::from_str::<Value>(&criminalip_result)?;
+            serde_json::to_string_pretty(&parsed_result)?
         } else {
-            query_criminalip_ip_data(target).await?
+            let criminalip_result = query_criminalip_ip_data(target).await?;
+            let parsed_result = serde_json::from_str::<Value>(&criminalip_result)?;
+            let record = parse_criminalip_ip_data(target, &parsed_result);
+            if options.summary {
+                record.summary_line()
+            } else {
+                serde_json::to_string_pretty(&record)?
+            }
         };
 
-        let parsed_result = serde_json::from_str::<Value>(&criminalip_result)?;
-
         match output_file {
             Some(file_path) => {
-                let mut file = File::create(file_path)?;
-                write!(file, "{}", serde_json::to_string_pretty(&parsed_result)?)?;
+                let mut file = open_output_file(file_path)?;
+                writeln!(file, "CriminalIP:\n{}", result)?;
             }
             None => {
                 println!("CriminalIP:");
-                println!("{}", serde_json::to_string_pretty(&parsed_result)?);
+                println!("{}", result);
             }
         }
     } else {
@@ -129,3 +220,51 @@ pub async fn run_single_search_criminalip(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn verdict_is_the_worse_known_score() {
+        let record = parse_criminalip_ip_data("192.0.2.1", &json!({
+            "score": {"inbound": "Low", "outbound": "Dangerous"},
+            "issues": {"is_vpn": true, "is_tor": false},
+        }));
+        let risk = record.risk.unwrap();
+        assert_eq!(risk.verdict, "Dangerous");
+        assert_eq!(risk.flags, ["vpn"]);
+    }
+
+    #[test]
+    fn unknown_scores_never_win_the_verdict() {
+        let risk = |inbound: &str, outbound: &str| {
+            parse_criminalip_ip_data("192.0.2.1", &json!({"score": {"inbound": inbound, "outbound": outbound}}))
+                .risk
+                .unwrap()
+                .verdict
+        };
+        assert_eq!(risk("Critcal", "Safe"), "Safe");
+        assert_eq!(risk("Moderate", "bogus"), "Moderate");
+        assert_eq!(risk("bogus", "other"), "Unknown");
+    }
+
+    #[test]
+    fn out_of_range_ports_are_skipped() {
+        let record = parse_criminalip_ip_data("192.0.2.1", &json!({
+            "port": {"data": [
+                {"open_port_no": 443, "socket": "tcp", "app_name": "nginx"},
+                {"open_port_no": 70000, "socket": "tcp"},
+                {"open_port_no": 443, "socket": "tcp", "app_name": "nginx"},
+            ]},
+            "vulnerability": {"data": [
+                {"cve_id": "CVE-2021-44228", "cvssv3_score": 10.0},
+                {"cve_id": "CVE-2021-44228", "cvssv3_score": 10.0},
+            ]},
+        }));
+        assert_eq!(record.services.len(), 1);
+        assert_eq!(record.services[0].port, 443);
+        assert_eq!(record.vulnerabilities.len(), 1);
+    }
+}
@@ -0,0 +1,135 @@
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::error::Error;
+use std::io::Write;
+use std::time::Duration;
+use crate::model::{CertificateRecord, SubdomainRecord};
+use crate::options::SearchOptions;
+use crate::target::is_domain;
+use crate::read_list::open_output_file;
+
+// Keyless, but large domains take crt.sh a while to answer
+pub async fn query_crtsh(domain: &str) -> Result<Value, Box<dyn Error>> {
+    let client = Client::builder().timeout(Duration::from_secs(120)).build()?;
+
+    let response = client
+        .get("https://crt.sh/")
+        .query(&[("q", format!("%.{}", domain)), ("output", "json".to_string())])
+        .send()
+        .await?
+        .error_for_status()?;
+
+    let response_body = response.text().await?;
+
+    Ok(serde_json::from_str(&response_body)?)
+}
+
+// name_value holds every SAN of a certificate separated by newlines, wildcards included
+pub fn parse_crtsh_names(domain: &str, entries: &[Value]) -> SubdomainRecord {
+    let domain = domain.to_lowercase();
+    let suffix = format!(".{}", domain);
+
+    let names: Vec<String> = entries
+        .iter()
+        .filter_map(|entry| entry["name_value"].as_str())
+        .flat_map(|name_value| name_value.lines())
+        .map(|name| name.trim().trim_start_matches("*.").to_lowercase())
+        .filter(|name| *name == domain || name.ends_with(&suffix))
+        .collect();
+
+    SubdomainRecord::new("crt.sh", &domain, names)
+}
+
+// One entry per certificate, newest first
+pub fn parse_crtsh_certificates(entries: &[Value]) -> Vec<CertificateRecord> {
+    let mut certificates: Vec<CertificateRecord> = Vec::new();
+
+    for entry in entries {
+        let id = match entry["id"].as_u64() {
+            Some(id) => id,
+            None => continue,
+        };
+        if certificates.iter().any(|cert| cert.id == id) {
+            continue;
+        }
+
+        certificates.push(CertificateRecord {
+            id,
+            common_name: entry["common_name"].as_str().map(String::from),
+            issuer: entry["issuer_name"].as_str().map(String::from),
+            not_before: entry["not_before"].as_str().map(String::from),
+            not_after: entry["not_after"].as_str().map(String::from),
+        });
+    }
+
+    certificates.sort_by(|a, b| b.not_before.cmp(&a.not_before));
+    certificates
+}
+
+pub async fn run_single_search_crtsh(
+    target: &str,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn Error>> {
+    if !is_domain(target) {
+        println!("Invalid target: {}", target);
+        return Ok(());
+    }
+
+    let crtsh_result = query_crtsh(target).await?;
+    let entries = crtsh_result.as_array().cloned().unwrap_or_default();
+
+    let subdomains = parse_crtsh_names(target, &entries);
+    let mut certificates = parse_crtsh_certificates(&entries);
+    if let Some(limit) = options.limit {
+        certificates.truncate(limit);
+    }
+
+    let crtsh_json = json!({
+        "subdomains": subdomains,
+        "certificates": certificates,
+    });
+
+    match output_file {
+        Some(file_path) => {
+            let mut file = open_output_file(file_path)?;
+            writeln!(file, "crt.sh:\n{}", serde_json::to_string_pretty(&crtsh_json)?)?;
+        }
+        None => {
+            println!("crt.sh:");
+            println!("{}", serde_json::to_string_pretty(&crtsh_json)?);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_split_unwildcarded_and_scoped_to_the_domain() {
+        let entries = vec![
+            json!({"name_value": "*.Example.com\nexample.com"}),
+            json!({"name_value": "www.example.com\nmail.example.com"}),
+            json!({"name_value": "example.com.evil.net\nnotexample.com"}),
+            json!({"id": 1}),
+        ];
+        let record = parse_crtsh_names("example.com", &entries);
+        assert_eq!(record.source, "crt.sh");
+        assert_eq!(record.subdomains, ["example.com", "mail.example.com", "www.example.com"]);
+    }
+
+    #[test]
+    fn certificates_are_deduplicated_newest_first() {
+        let entries = vec![
+            json!({"id": 1, "common_name": "example.com", "not_before": "2023-01-01T00:00:00"}),
+            json!({"id": 2, "common_name": "www.example.com", "issuer_name": "C=US, O=Let's Encrypt", "not_before": "2024-01-01T00:00:00"}),
+            json!({"id": 1, "common_name": "example.com", "not_before": "2023-01-01T00:00:00"}),
+            json!({"common_name": "no-id.example.com"}),
+        ];
+        let certificates = parse_crtsh_certificates(&entries);
+        assert_eq!(certificates.iter().map(|cert| cert.id).collect::<Vec<_>>(), [2, 1]);
+        assert_eq!(certificates[0].issuer.as_deref(), Some("C=US, O=Let's Encrypt"));
+    }
+}
@@ -0,0 +1,247 @@
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use crate::model::Vulnerability;
+
+// CVE details read from locally imported feeds, so enrichment works without network access
+#[derive(Debug, Default)]
+pub struct CveFeed {
+    entries: HashMap<String, CveEntry>,
+    known_exploited: HashSet<String>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct CveEntry {
+    cvss: Option<f64>,
+    severity: Option<String>,
+    summary: Option<String>,
+}
+
+impl CveFeed {
+    // Accepts NVD JSON 1.1 yearly feeds (CVE_Items) and NVD API 2.0 dumps (vulnerabilities)
+    pub fn load_nvd<P: AsRef<Path>>(&mut self, path: P) -> Result<usize, Box<dyn Error>> {
+        let feed: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let before = self.entries.len();
+
+        if let Some(items) = feed["CVE_Items"].as_array() {
+            for item in items {
+                if let Some(id) = item["cve"]["CVE_data_meta"]["ID"].as_str() {
+                    self.entries.insert(id.to_string(), parse_nvd_v1_item(item));
+                }
+            }
+        } else if let Some(items) = feed["vulnerabilities"].as_array() {
+            for item in items {
+                if let Some(id) = item["cve"]["id"].as_str() {
+                    self.entries.insert(id.to_string(), parse_nvd_v2_item(&item["cve"]));
+                }
+            }
+        } else {
+            return Err("unrecognised NVD feed format".into());
+        }
+
+        Ok(self.entries.len() - before)
+    }
+
+    // CISA Known Exploited Vulnerabilities catalog (known_exploited_vulnerabilities.json)
+    pub fn load_kev<P: AsRef<Path>>(&mut self, path: P) -> Result<usize, Box<dyn Error>> {
+        let catalog: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let items = catalog["vulnerabilities"]
+            .as_array()
+            .ok_or("unrecognised KEV catalog format")?;
+
+        for item in items {
+            if let Some(id) = item["cveID"].as_str() {
+                self.known_exploited.insert(id.to_string());
+            }
+        }
+
+        Ok(items.len())
+    }
+
+    pub fn lookup(&self, id: &str) -> Vulnerability {
+        let entry = self.entries.get(id).cloned().unwrap_or_default();
+        Vulnerability {
+            id: id.to_string(),
+            cvss: entry.cvss,
+            severity: entry.severity,
+            summary: entry.summary,
+            known_exploited: self.known_exploited.contains(id),
+            cpe: None,
+        }
+    }
+
+    // Enriched, filtered by min_cvss and sorted highest score first, known exploited
+    // breaking ties. CVEs without a score are dropped once a minimum is set.
+    pub fn enrich(&self, ids: &[String], min_cvss: Option<f64>) -> Vec<Vulnerability> {
+        let mut vulns: Vec<Vulnerability> = ids
+            .iter()
+            .map(|id| self.lookup(id))
+            .filter(|vuln| match min_cvss {
+                Some(min) => vuln.cvss.is_some_and(|score| score >= min),
+                None => true,
+            })
+            .collect();
+
+        sort_vulnerabilities(&mut vulns);
+        vulns
+    }
+
+    // Replaces a ["CVE-..."] array under json[field] with enriched entries,
+    // as returned by InternetDB and the Shodan host endpoint
+    pub fn enrich_field(&self, json: &mut Value, field: &str, min_cvss: Option<f64>) -> Result<(), Box<dyn Error>> {
+        let ids: Vec<String> = match json[field].as_array() {
+            Some(ids) => ids.iter().filter_map(|id| id.as_str().map(String::from)).collect(),
+            None => return Ok(()),
+        };
+
+        json[field] = serde_json::to_value(self.enrich(&ids, min_cvss))?;
+        Ok(())
+    }
+}
+
+pub fn sort_vulnerabilities(vulns: &mut [Vulnerability]) {
+    vulns.sort_by(|a, b| {
+        b.cvss
+            .unwrap_or(-1.0)
+            .total_cmp(&a.cvss.unwrap_or(-1.0))
+            .then(b.known_exploited.cmp(&a.known_exploited))
+            .then(a.id.cmp(&b.id))
+    });
+}
+
+fn parse_nvd_v1_item(item: &Value) -> CveEntry {
+    let v3 = &item["impact"]["baseMetricV3"]["cvssV3"];
+    let v2 = &item["impact"]["baseMetricV2"];
+
+    let (cvss, severity) = if v3["baseScore"].is_number() {
+        (v3["baseScore"].as_f64(), v3["baseSeverity"].as_str())
+    } else {
+        (v2["cvssV2"]["baseScore"].as_f64(), v2["severity"].as_str())
+    };
+
+    CveEntry {
+        cvss,
+        severity: severity.map(String::from),
+        summary: item["cve"]["description"]["description_data"]
+            .as_array()
+            .and_then(|descriptions| english_description(descriptions)),
+    }
+}
+
+fn parse_nvd_v2_item(cve: &Value) -> CveEntry {
+    let metrics = &cve["metrics"];
+    let metric = ["cvssMetricV31", "cvssMetricV30", "cvssMetricV2"]
+        .iter()
+        .find_map(|key| metrics[*key].as_array().and_then(|list| list.first()));
+
+    let (cvss, severity) = match metric {
+        Some(metric) => (
+            metric["cvssData"]["baseScore"].as_f64(),
+            // v3 keeps the severity inside cvssData, v2 alongside it
+            metric["cvssData"]["baseSeverity"]
+                .as_str()
+                .or_else(|| metric["baseSeverity"].as_str()),
+        ),
+        None => (None, None),
+    };
+
+    CveEntry {
+        cvss,
+        severity: severity.map(String::from),
+        summary: cve["descriptions"]
+            .as_array()
+            .and_then(|descriptions| english_description(descriptions)),
+    }
+}
+
+fn english_description(descriptions: &[Value]) -> Option<String> {
+    descriptions
+        .iter()
+        .find(|description| description["lang"] == "en")
+        .or_else(|| descriptions.first())
+        .and_then(|description| description["value"].as_str())
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn write_feed(name: &str, feed: &Value) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("rust_recon_{}_{}.json", name, std::process::id()));
+        fs::write(&path, feed.to_string()).unwrap();
+        path
+    }
+
+    fn test_feed(test: &str) -> CveFeed {
+        let nvd_v1 = write_feed(&format!("{}_nvd_v1", test), &json!({"CVE_Items": [{
+            "cve": {
+                "CVE_data_meta": {"ID": "CVE-2021-44228"},
+                "description": {"description_data": [{"lang": "en", "value": "Log4Shell"}]}
+            },
+            "impact": {"baseMetricV3": {"cvssV3": {"baseScore": 10.0, "baseSeverity": "CRITICAL"}}}
+        }]}));
+        let nvd_v2 = write_feed(&format!("{}_nvd_v2", test), &json!({"vulnerabilities": [{"cve": {
+            "id": "CVE-2014-0160",
+            "descriptions": [{"lang": "es", "value": "Heartbleed (es)"}, {"lang": "en", "value": "Heartbleed"}],
+            "metrics": {"cvssMetricV2": [{"cvssData": {"baseScore": 5.0}, "baseSeverity": "MEDIUM"}]}
+        }}]}));
+        let kev = write_feed(&format!("{}_kev", test), &json!({"vulnerabilities": [{"cveID": "CVE-2014-0160"}]}));
+
+        let mut feed = CveFeed::default();
+        assert_eq!(feed.load_nvd(&nvd_v1).unwrap(), 1);
+        assert_eq!(feed.load_nvd(&nvd_v2).unwrap(), 1);
+        assert_eq!(feed.load_kev(&kev).unwrap(), 1);
+        for path in [nvd_v1, nvd_v2, kev] {
+            fs::remove_file(path).unwrap();
+        }
+        feed
+    }
+
+    #[test]
+    fn both_nvd_formats_and_kev_are_loaded() {
+        let feed = test_feed("load");
+        let log4shell = feed.lookup("CVE-2021-44228");
+        assert_eq!(log4shell.cvss, Some(10.0));
+        assert_eq!(log4shell.severity.as_deref(), Some("CRITICAL"));
+        assert_eq!(log4shell.summary.as_deref(), Some("Log4Shell"));
+        assert!(!log4shell.known_exploited);
+
+        let heartbleed = feed.lookup("CVE-2014-0160");
+        assert_eq!(heartbleed.cvss, Some(5.0));
+        assert_eq!(heartbleed.summary.as_deref(), Some("Heartbleed"));
+        assert!(heartbleed.known_exploited);
+    }
+
+    #[test]
+    fn enrich_sorts_by_score_and_applies_min_cvss() {
+        let feed = test_feed("enrich");
+        let ids: Vec<String> = ["CVE-2014-0160", "CVE-1999-0001", "CVE-2021-44228"].map(String::from).to_vec();
+
+        let all = feed.enrich(&ids, None);
+        assert_eq!(
+            all.iter().map(|vuln| vuln.id.as_str()).collect::<Vec<_>>(),
+            ["CVE-2021-44228", "CVE-2014-0160", "CVE-1999-0001"]
+        );
+
+        let severe = feed.enrich(&ids, Some(7.0));
+        assert_eq!(severe.len(), 1);
+        assert_eq!(severe[0].id, "CVE-2021-44228");
+    }
+
+    #[test]
+    fn enrich_field_replaces_bare_ids() {
+        let feed = test_feed("enrich_field");
+        let mut host = json!({"ip": "192.0.2.1", "vulns": ["CVE-2014-0160"]});
+        feed.enrich_field(&mut host, "vulns", None).unwrap();
+        assert_eq!(host["vulns"][0]["id"], "CVE-2014-0160");
+        assert_eq!(host["vulns"][0]["known_exploited"], true);
+
+        let mut no_vulns = json!({"ip": "192.0.2.2"});
+        feed.enrich_field(&mut no_vulns, "vulns", None).unwrap();
+        assert!(no_vulns.get("vulns").is_none());
+    }
+}
@@ -0,0 +1,230 @@
+use hickory_resolver::config::{NameServerConfigGroup, ResolverConfig, ResolverOpts};
+use hickory_resolver::error::{ResolveError, ResolveErrorKind};
+use hickory_resolver::proto::op::ResponseCode;
+use hickory_resolver::proto::rr::{RData, RecordType};
+use hickory_resolver::TokioAsyncResolver;
+use std::error::Error;
+use std::io::Write;
+use std::net::{IpAddr, SocketAddr};
+use crate::model::DnsReport;
+use crate::options::SearchOptions;
+use crate::target::is_domain;
+use crate::read_list::open_output_file;
+
+const DNS_RECORD_TYPES: [RecordType; 8] = [
+    RecordType::A,
+    RecordType::AAAA,
+    RecordType::CNAME,
+    RecordType::MX,
+    RecordType::NS,
+    RecordType::TXT,
+    RecordType::SOA,
+    RecordType::CAA,
+];
+
+// --resolver takes "192.0.2.53", "127.0.0.1:5353" or "[::1]:5353"; the port defaults to 53
+pub fn parse_resolver(value: &str) -> Option<SocketAddr> {
+    value
+        .parse::<SocketAddr>()
+        .ok()
+        .or_else(|| value.parse::<IpAddr>().ok().map(|ip| SocketAddr::new(ip, 53)))
+}
+
+// The system resolver unless --resolver points somewhere else
+pub fn build_resolver(resolver: Option<SocketAddr>) -> Result<TokioAsyncResolver, Box<dyn Error>> {
+    match resolver {
+        Some(addr) => {
+            let name_servers = NameServerConfigGroup::from_ips_clear(&[addr.ip()], addr.port(), true);
+            let config = ResolverConfig::from_parts(None, vec![], name_servers);
+            Ok(TokioAsyncResolver::tokio(config, ResolverOpts::default()))
+        }
+        None => Ok(TokioAsyncResolver::tokio_from_system_conf()?),
+    }
+}
+
+fn is_nxdomain(err: &ResolveError) -> bool {
+    matches!(
+        err.kind(),
+        ResolveErrorKind::NoRecordsFound { response_code, .. } if *response_code == ResponseCode::NXDomain
+    )
+}
+
+// Records of one type as text; a missing name or type is an empty list, not an error
+pub async fn lookup_records(
+    resolver: &TokioAsyncResolver,
+    name: &str,
+    record_type: RecordType,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let lookup = match resolver.lookup(name, record_type).await {
+        Ok(lookup) => lookup,
+        Err(err) if matches!(err.kind(), ResolveErrorKind::NoRecordsFound { .. }) => return Ok(Vec::new()),
+        Err(err) => return Err(Box::new(err)),
+    };
+
+    // A and AAAA answers also carry the CNAME chain that led to them
+    let mut values: Vec<String> = Vec::new();
+    for record in lookup.record_iter().filter(|record| record.record_type() == record_type) {
+        let value = match record.data() {
+            // TXT strings are split into 255 byte chunks on the wire
+            Some(RData::TXT(txt)) => txt.txt_data().iter().map(|data| String::from_utf8_lossy(data)).collect(),
+            Some(data) => data.to_string(),
+            None => continue,
+        };
+        if !values.contains(&value) {
+            values.push(value);
+        }
+    }
+
+    Ok(values)
+}
+
+// A CNAME is dangling when its target no longer exists, which often means the
+// service it pointed at was deprovisioned and the name can be taken over
+async fn find_dangling_cnames(resolver: &TokioAsyncResolver, cnames: &[String]) -> Vec<String> {
+    let mut dangling = Vec::new();
+
+    for cname in cnames {
+        if let Err(err) = resolver.lookup_ip(cname.as_str()).await {
+            if is_nxdomain(&err) {
+                dangling.push(cname.clone());
+            }
+        }
+    }
+
+    dangling
+}
+
+// The SPF policy among a domain's TXT records, e.g. "v=spf1 include:_spf.example.com -all"
+pub fn find_spf(txt: &[String]) -> Option<String> {
+    txt.iter().find(|value| value.to_lowercase().starts_with("v=spf1")).cloned()
+}
+
+// The DMARC record among the TXT records published at _dmarc.<domain>
+pub fn find_dmarc(txt: &[String]) -> Option<String> {
+    txt.iter().find(|value| value.to_lowercase().starts_with("v=dmarc1")).cloned()
+}
+
+// The p= tag of a DMARC record: none, quarantine or reject
+pub fn dmarc_policy(dmarc: &str) -> Option<String> {
+    dmarc
+        .split(';')
+        .filter_map(|tag| tag.trim().split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("p"))
+        .map(|(_, policy)| policy.trim().to_lowercase())
+}
+
+pub async fn search_dns(domain: &str, options: &SearchOptions) -> Result<DnsReport, Box<dyn Error>> {
+    let resolver = build_resolver(options.resolver)?;
+    // Fully qualified so the resolver's search domains are never appended
+    let fqdn = format!("{}.", domain.trim_end_matches('.'));
+
+    let mut report = DnsReport {
+        source: "DNS".to_string(),
+        domain: domain.to_string(),
+        resolver: options.resolver.map_or("system".to_string(), |addr| addr.to_string()),
+        ..Default::default()
+    };
+
+    // A timeout or bad answer for one type is recorded, so the rest of the report
+    // (and the A/AAAA addresses the IP pivot needs) still comes through
+    for record_type in DNS_RECORD_TYPES {
+        match lookup_records(&resolver, &fqdn, record_type).await {
+            Ok(values) if values.is_empty() => {}
+            Ok(values) => {
+                report.records.insert(record_type.to_string(), values);
+            }
+            Err(err) => {
+                report.errors.insert(record_type.to_string(), err.to_string());
+            }
+        }
+    }
+
+    report.spf = report.records.get("TXT").and_then(|txt| find_spf(txt));
+
+    match lookup_records(&resolver, &format!("_dmarc.{}", fqdn), RecordType::TXT).await {
+        Ok(dmarc) => report.dmarc = find_dmarc(&dmarc),
+        Err(err) => {
+            report.errors.insert("DMARC".to_string(), err.to_string());
+        }
+    }
+    report.dmarc_policy = report.dmarc.as_deref().and_then(dmarc_policy);
+
+    if let Some(cnames) = report.records.get("CNAME") {
+        report.dangling_cnames = find_dangling_cnames(&resolver, cnames).await;
+    }
+
+    for record_type in ["A", "AAAA"] {
+        report.resolved_ips.extend(report.records.get(record_type).cloned().unwrap_or_default());
+    }
+
+    Ok(report)
+}
+
+// Returns the addresses the domain resolved to, so they can be searched as IP targets
+pub async fn run_single_search_dns(
+    target: &str,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    if !is_domain(target) {
+        println!("Invalid target: {}", target);
+        return Ok(Vec::new());
+    }
+
+    let report = search_dns(target, options).await?;
+    let dns_result = if options.summary {
+        report.summary_line()
+    } else {
+        serde_json::to_string_pretty(&report)?
+    };
+
+    match output_file {
+        Some(file_path) => {
+            let mut file = open_output_file(file_path)?;
+            writeln!(file, "DNS:\n{}", dns_result)?;
+        }
+        None => {
+            println!("DNS:");
+            println!("{}", dns_result);
+        }
+    }
+
+    Ok(report.resolved_ips)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn resolvers_default_to_port_53() {
+        assert_eq!(parse_resolver("192.0.2.53"), Some("192.0.2.53:53".parse().unwrap()));
+        assert_eq!(parse_resolver("127.0.0.1:5353"), Some("127.0.0.1:5353".parse().unwrap()));
+        assert_eq!(parse_resolver("[::1]:5353"), Some("[::1]:5353".parse().unwrap()));
+        assert_eq!(parse_resolver("2001:db8::53"), Some("[2001:db8::53]:53".parse().unwrap()));
+        assert_eq!(parse_resolver("dns.example.com"), None);
+        assert_eq!(parse_resolver("127.0.0.1:99999"), None);
+    }
+
+    #[test]
+    fn spf_is_picked_out_of_the_txt_records() {
+        let txt = records(&["google-site-verification=abc", "V=SPF1 include:_spf.example.com -all"]);
+        assert_eq!(find_spf(&txt).as_deref(), Some("V=SPF1 include:_spf.example.com -all"));
+        assert_eq!(find_spf(&records(&["v=spf2.0/pra"])), None);
+        assert_eq!(find_spf(&[]), None);
+    }
+
+    #[test]
+    fn dmarc_policy_is_read_from_the_p_tag() {
+        let txt = records(&["unrelated", "v=DMARC1; sp=none; P = Reject ; rua=mailto:dmarc@example.com"]);
+        let dmarc = find_dmarc(&txt).unwrap();
+        assert_eq!(dmarc_policy(&dmarc).as_deref(), Some("reject"));
+        assert_eq!(dmarc_policy("v=DMARC1; p=quarantine").as_deref(), Some("quarantine"));
+        assert_eq!(dmarc_policy("v=DMARC1; sp=none"), None);
+        assert_eq!(find_dmarc(&records(&["v=spf1 -all"])), None);
+    }
+}
@@ -0,0 +1,22 @@
+use std::error::Error;
+use std::fmt;
+
+// Error returned by the provider modules for failed requests and unusable responses
+#[derive(Debug)]
+pub struct SearchError {
+    message: String,
+}
+
+impl SearchError {
+    pub fn new(message: String) -> Self {
+        Self { message }
+    }
+}
+
+impl Error for SearchError {}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
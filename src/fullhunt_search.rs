@@ -1,12 +1,14 @@
 use regex::Regex;
 use reqwest::header::HeaderMap;
 use reqwest::Client;
-use serde_json::{self, Value};
+use serde_json::{self, json, Value};
 use std::env;
-use std::fs::File;
 use std::io::Write;
+use crate::model::{value_to_string, HostRecord, Service, SubdomainRecord};
+use crate::options::SearchOptions;
+use crate::read_list::open_output_file;
 
-pub async fn query_fullhunt(domain: &str) -> Result<String, Box<dyn std::error::Error>> {
+async fn query_fullhunt_path(path: &str) -> Result<String, Box<dyn std::error::Error>> {
     let api_key = env::var("FULLHUNT_API").expect("FULLHUNT_API not found");
 
     let client = Client::new();
@@ -14,7 +16,7 @@ pub async fn query_fullhunt(domain: &str) -> Result<String, Box<dyn std::error::
     let mut headers = HeaderMap::new();
     headers.insert("X-API-KEY", api_key.parse().unwrap());
 
-    let url = format!("https://fullhunt.io/api/v1/domain/{}/details", domain);
+    let url = format!("https://fullhunt.io/api/v1/{}", path);
 
     let response = client.get(&url).headers(headers).send().await?;
 
@@ -26,6 +28,67 @@ pub async fn query_fullhunt(domain: &str) -> Result<String, Box<dyn std::error::
     Ok(pretty_response)
 }
 
+pub async fn query_fullhunt(domain: &str) -> Result<String, Box<dyn std::error::Error>> {
+    query_fullhunt_path(&format!("domain/{}/details", domain)).await
+}
+
+pub async fn query_fullhunt_subdomains(domain: &str) -> Result<String, Box<dyn std::error::Error>> {
+    query_fullhunt_path(&format!("domain/{}/subdomains", domain)).await
+}
+
+pub async fn query_fullhunt_host(host: &str) -> Result<String, Box<dyn std::error::Error>> {
+    query_fullhunt_path(&format!("host/{}", host)).await
+}
+
+// Maps one FullHunt host entry (from details hosts[] or /host) onto the normalized host model
+pub fn parse_fullhunt_host(host: &Value) -> HostRecord {
+    let mut record = HostRecord {
+        source: "FullHunt".to_string(),
+        ip: value_to_string(&host["ip_address"]),
+        ..Default::default()
+    };
+
+    if let Some(name) = host["host"].as_str() {
+        record.hostnames.push(name.to_string());
+    }
+
+    let ports = host["network_ports"]
+        .as_array()
+        .or_else(|| host["ports"].as_array())
+        .cloned()
+        .unwrap_or_default();
+    for port in ports.iter().filter_map(|port| port.as_u64().and_then(|p| u16::try_from(p).ok())) {
+        let service = Service {
+            port,
+            ..Default::default()
+        };
+        if !record.services.contains(&service) {
+            record.services.push(service);
+        }
+    }
+
+    record.technologies = string_list(&host["products"]);
+    record.tags = string_list(&host["tags"]);
+
+    if host["is_cdn"].as_bool().unwrap_or(false) {
+        let cdn = value_to_string(&host["cdn"]).unwrap_or_else(|| "unknown".to_string());
+        record.tags.push(format!("cdn:{}", cdn));
+    }
+    if host["is_cloud"].as_bool().unwrap_or(false) {
+        let provider = value_to_string(&host["cloud"]["provider"]).unwrap_or_else(|| "unknown".to_string());
+        record.tags.push(format!("cloud:{}", provider));
+    }
+
+    record
+}
+
+fn string_list(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|items| items.iter().filter_map(value_to_string).collect())
+        .unwrap_or_default()
+}
+
 pub fn is_domain(target: &str) -> bool {
     let domain_regex =
         Regex::new(r"^(?:[a-zA-Z0-9](?:[a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?\.)+[a-zA-Z]{2,}$").unwrap();
@@ -36,21 +99,45 @@ pub fn is_domain(target: &str) -> bool {
 pub async fn run_single_search_fullhunt(
     target: &str,
     output_file: Option<&str>,
+    options: &SearchOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
     if is_domain(target) {
-        let fullhunt_result = query_fullhunt(target).await?;
-        let parsed_result = serde_json::from_str::<Value>(&fullhunt_result)?;
+        let fullhunt_json = if options.fullhunt_host {
+            let host_result = query_fullhunt_host(target).await?;
+            let parsed_result = serde_json::from_str::<Value>(&host_result)?;
+            serde_json::to_value(parse_fullhunt_host(&parsed_result))?
+        } else {
+            let details_result = query_fullhunt(target).await?;
+            let details = serde_json::from_str::<Value>(&details_result)?;
+            let hosts: Vec<HostRecord> = details["hosts"]
+                .as_array()
+                .map(|hosts| hosts.iter().map(parse_fullhunt_host).collect())
+                .unwrap_or_default();
+
+            let subdomains_result = query_fullhunt_subdomains(target).await?;
+            let subdomains = serde_json::from_str::<Value>(&subdomains_result)?;
+            let names = subdomains["hosts"]
+                .as_array()
+                .map(|names| names.iter().filter_map(|name| name.as_str()).collect::<Vec<_>>())
+                .unwrap_or_default();
+
+            json!({
+                "hosts": hosts,
+                "subdomains": SubdomainRecord::new("FullHunt", target, names),
+            })
+        };
+
         if let Some(output_file) = output_file {
-            let mut file = File::create(output_file)?;
+            let mut file = open_output_file(output_file)?;
             writeln!(
                 file,
                 "FullHunt: \n{}",
-                serde_json::to_string_pretty(&parsed_result)?
+                serde_json::to_string_pretty(&fullhunt_json)?
             )?;
         } else {
             println!(
                 "FullHunt: \n{}",
-                serde_json::to_string_pretty(&parsed_result)?
+                serde_json::to_string_pretty(&fullhunt_json)?
             );
         }
     } else {
@@ -58,3 +145,30 @@ pub async fn run_single_search_fullhunt(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hosts_without_an_address_have_no_ip() {
+        let record = parse_fullhunt_host(&json!({
+            "host": "dev.example.com",
+            "network_ports": [443, 443, 80, 70000],
+            "products": ["nginx"],
+            "is_cdn": true,
+            "cdn": "cloudflare",
+        }));
+        assert_eq!(record.ip, None);
+        assert_eq!(record.hostnames, ["dev.example.com"]);
+        assert_eq!(record.services.iter().map(|s| s.port).collect::<Vec<_>>(), [443, 80]);
+        assert_eq!(record.tags, ["cdn:cloudflare"]);
+        assert!(record.summary_line().starts_with("dev.example.com  "));
+    }
+
+    #[test]
+    fn host_addresses_are_kept() {
+        let record = parse_fullhunt_host(&json!({"host": "www.example.com", "ip_address": "192.0.2.1"}));
+        assert_eq!(record.ip.as_deref(), Some("192.0.2.1"));
+    }
+}
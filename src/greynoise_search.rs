@@ -0,0 +1,212 @@
+use reqwest::{Client, StatusCode};
+use std::env;
+use std::error::Error;
+use std::io::Write;
+use std::net::IpAddr;
+use serde_json::Value;
+use crate::model::{value_to_string, IndicatorReport};
+use crate::options::SearchOptions;
+use crate::error::SearchError;
+use crate::read_list::open_output_file;
+
+pub fn is_ip(target: &str) -> bool {
+    target.parse::<IpAddr>().is_ok()
+}
+
+// Optional; without it only the community endpoint is used
+pub fn get_greynoise_api_key() -> Option<String> {
+    env::var("GREYNOISE_API").ok()
+}
+
+// GreyNoise answers 404 with a JSON body for IPs it has never seen, so that is not an error
+async fn query_greynoise(url: &str, api_key: Option<&str>) -> Result<(StatusCode, Value), Box<dyn Error>> {
+    let client = Client::new();
+
+    let mut request = client.get(url).header("accept", "application/json");
+    if let Some(api_key) = api_key {
+        request = request.header("key", api_key);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|err| SearchError::new(err.to_string()))?;
+    let status = response.status();
+
+    let response_body = response
+        .text()
+        .await
+        .map_err(|err| SearchError::new(err.to_string()))?;
+
+    Ok((status, serde_json::from_str(&response_body).unwrap_or(Value::Null)))
+}
+
+pub async fn query_greynoise_community(ip: &str, api_key: Option<&str>) -> Result<Value, Box<dyn Error>> {
+    let url = format!("https://api.greynoise.io/v3/community/{}", ip);
+    let (status, result) = query_greynoise(&url, api_key).await?;
+
+    if status.is_success() || status == StatusCode::NOT_FOUND {
+        Ok(result)
+    } else {
+        Err(Box::new(SearchError::new(format!(
+            "GreyNoise community returned HTTP {}: {}",
+            status,
+            result["message"].as_str().unwrap_or("")
+        ))))
+    }
+}
+
+// Context plus the RIOT lookup, which the enterprise API serves separately.
+// Returns None when the key is not entitled to them, so the caller can fall back.
+pub async fn query_greynoise_enterprise(ip: &str, api_key: &str) -> Result<Option<Value>, Box<dyn Error>> {
+    let context_url = format!("https://api.greynoise.io/v2/noise/context/{}", ip);
+    let (status, mut context) = query_greynoise(&context_url, Some(api_key)).await?;
+
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => return Ok(None),
+        status if !status.is_success() && status != StatusCode::NOT_FOUND => {
+            return Err(Box::new(SearchError::new(format!(
+                "GreyNoise context returned HTTP {}",
+                status
+            ))));
+        }
+        _ => {}
+    }
+
+    let riot_url = format!("https://api.greynoise.io/v2/riot/{}", ip);
+    let (_, riot) = query_greynoise(&riot_url, Some(api_key)).await?;
+
+    if !context.is_object() {
+        context = serde_json::json!({ "ip": ip });
+    }
+    // Context only reports seen=true for scanners; noise mirrors the community field
+    context["noise"] = Value::Bool(context["seen"].as_bool().unwrap_or(false));
+    context["riot"] = Value::Bool(riot["riot"].as_bool().unwrap_or(false));
+    if context["name"].is_null() {
+        context["name"] = riot["name"].clone();
+    }
+
+    Ok(Some(context))
+}
+
+// Both endpoints share classification/noise/riot/last_seen; the actor is "name" in
+// community results and "actor" in context results
+pub fn parse_greynoise(ip: &str, result: &Value) -> IndicatorReport {
+    let mut report = IndicatorReport::new("GreyNoise", ip, "ip");
+
+    report.classification = value_to_string(&result["classification"]);
+
+    report.details.insert("noise".to_string(), Value::Bool(result["noise"].as_bool().unwrap_or(false)));
+    report.details.insert("riot".to_string(), Value::Bool(result["riot"].as_bool().unwrap_or(false)));
+
+    let actor = value_to_string(&result["actor"])
+        .filter(|actor| actor != "unknown")
+        .or_else(|| value_to_string(&result["name"]).filter(|name| name != "unknown"));
+    if let Some(actor) = actor {
+        report.details.insert("actor".to_string(), Value::String(actor));
+    }
+
+    for key in ["last_seen", "first_seen", "link", "message"] {
+        if let Some(value) = value_to_string(&result[key]) {
+            report.details.insert(key.to_string(), Value::String(value));
+        }
+    }
+
+    report.tags = result["tags"]
+        .as_array()
+        .map(|tags| tags.iter().filter_map(value_to_string).collect())
+        .unwrap_or_default();
+
+    report
+}
+
+// The shared summary plus GreyNoise's noise/riot flags and actor
+pub fn greynoise_summary_line(report: &IndicatorReport) -> String {
+    let mut line = report.summary_line();
+
+    for flag in ["noise", "riot"] {
+        if report.details.get(flag).and_then(Value::as_bool) == Some(true) {
+            line.push_str(&format!("  {}", flag));
+        }
+    }
+    if let Some(actor) = report.details.get("actor").and_then(Value::as_str) {
+        line.push_str(&format!("  actor={}", actor));
+    }
+
+    line
+}
+
+pub async fn search_greynoise(ip: &str) -> Result<IndicatorReport, Box<dyn Error>> {
+    let api_key = get_greynoise_api_key();
+
+    let enterprise = match &api_key {
+        Some(api_key) => query_greynoise_enterprise(ip, api_key).await?,
+        None => None,
+    };
+
+    let result = match enterprise {
+        Some(context) => context,
+        None => query_greynoise_community(ip, api_key.as_deref()).await?,
+    };
+
+    Ok(parse_greynoise(ip, &result))
+}
+
+pub async fn run_single_search_greynoise(
+    target: &str,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn Error>> {
+    if !is_ip(target) {
+        println!("Invalid target: {}", target);
+        return Ok(());
+    }
+
+    let report = search_greynoise(target).await?;
+
+    let greynoise_result = if options.summary {
+        greynoise_summary_line(&report)
+    } else {
+        serde_json::to_string_pretty(&report)?
+    };
+
+    match output_file {
+        Some(file_path) => {
+            let mut file = open_output_file(file_path)?;
+            writeln!(file, "GreyNoise:\n{}", greynoise_result)?;
+        }
+        None => {
+            println!("GreyNoise:");
+            println!("{}", greynoise_result);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn community_result_summary_shows_flags_and_actor() {
+        let result = json!({
+            "ip": "192.0.2.1",
+            "noise": true,
+            "riot": false,
+            "classification": "benign",
+            "name": "Example Scanner",
+            "last_seen": "2024-01-01"
+        });
+        let report = parse_greynoise("192.0.2.1", &result);
+        assert_eq!(report.classification.as_deref(), Some("benign"));
+        assert_eq!(greynoise_summary_line(&report), "192.0.2.1 (ip)  benign  noise  actor=Example Scanner");
+    }
+
+    #[test]
+    fn unknown_actor_is_dropped() {
+        let report = parse_greynoise("192.0.2.1", &json!({"noise": false, "riot": true, "name": "unknown"}));
+        assert!(!report.details.contains_key("actor"));
+        assert_eq!(greynoise_summary_line(&report), "192.0.2.1 (ip)  riot");
+    }
+}
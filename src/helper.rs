@@ -1,7 +1,7 @@
 pub fn print_help() {
     println!("Usage: RustRecon [command]");
     println!("Options:");
-    println!("  -t                Specify the target (IP, domain)");
+    println!("  -t                Specify the target (IP, domain, URL, email, hash)");
     println!("  -l                List of targets (IPs or domains");
     println!("  -o                Output the results to a file");
     println!("  -all              Run all applicable search types on target");
@@ -14,9 +14,19 @@ pub fn print_help() {
     println!("  fullhunt          Domain search using FullHunt");
     println!("  projectdiscovery  Domain search using ProjectDiscovery");
     println!("  criminalip        IP search using CriminalIP");
-    println!("  hunterio          Domain search using HunterIO");
+    println!("  hunterio          Domain and email search using HunterIO");
     println!("  netlas            IP or domain search using Netlas");
-    println!("  zoomeye           IP search using ZoomEye");
+    println!("  zoomeye           IP or domain search using ZoomEye");
     println!("  internetdb        Vulnerability check for IP using internetdb");
-    println!("  virustotal        IP or domain search using VirusTotal");
+    println!("  virustotal        IP, domain, URL or file hash search using VirusTotal");
+    println!("  urlscan           URL, domain or IP scan history using urlscan.io");
+    println!("  crtsh             Subdomain search using crt.sh certificate transparency logs");
+    println!("  securitytrails    Subdomains, DNS history and whois for domains, nearby IPs using SecurityTrails");
+    println!("  otx               IP, domain or file hash threat intelligence using AlienVault OTX");
+    println!("  greynoise         Scanner noise classification for IPs using GreyNoise");
+    println!("  abuseipdb         Abuse reports for IPs and CIDR blocks using AbuseIPDB");
+    println!("  binaryedge        IP services, CVEs and score, or domain subdomains using BinaryEdge");
+    println!("  rdap              IP and domain registration data using RDAP (no API key needed)");
+    println!("  dns               Domain DNS records, SPF/DMARC and dangling CNAME check using a DNS resolver");
+    println!("  wayback           Historic URLs, parameters, extensions and hosts using the Wayback Machine");
 }
\ No newline at end of file
@@ -1,44 +1,248 @@
-use serde_json::Value;
-use std::fs::File;
+use serde_json::{json, Value};
 use std::io::Write;
 use std::env;
 use std::error::Error;
+use std::future::Future;
 use regex::Regex;
+use crate::options::SearchOptions;
+use crate::read_list::open_output_file;
+
+// Free plans reject domain-search pages larger than 10
+const HUNTERIO_PAGE_SIZE: usize = 10;
 
 pub fn is_domain(target: &str) -> bool {
     let domain_regex = Regex::new(r"^([a-zA-Z0-9]+(-[a-zA-Z0-9]+)*\.)+[a-zA-Z]{2,}$").unwrap();
     domain_regex.is_match(target)
 }
 
-pub async fn query_hunterio(domain: &str) -> Result<String, Box<dyn Error>> {
+pub fn is_email(target: &str) -> bool {
+    match target.split_once('@') {
+        Some((user, domain)) => !user.is_empty() && !user.contains(char::is_whitespace) && is_domain(domain),
+        None => false,
+    }
+}
+
+async fn query_hunterio_endpoint(endpoint: &str, params: &[(&str, &str)]) -> Result<Value, Box<dyn Error>> {
     let api_key = env::var("HUNTERIO_API").expect("HUNTERIO_API not found");
 
     let client = reqwest::Client::new();
 
-    let url = format!("https://api.hunter.io/v2/domain-search?domain={}&api_key={}", domain, api_key);
+    let url = format!("https://api.hunter.io/v2/{}", endpoint);
+
+    let response = client
+        .get(&url)
+        .query(params)
+        .query(&[("api_key", api_key)])
+        .send()
+        .await?;
+    let response_body = response.text().await?;
+
+    Ok(serde_json::from_str(&response_body)?)
+}
+
+pub async fn query_hunterio(domain: &str, limit: usize, offset: usize) -> Result<Value, Box<dyn Error>> {
+    query_hunterio_endpoint(
+        "domain-search",
+        &[
+            ("domain", domain),
+            ("limit", &limit.to_string()),
+            ("offset", &offset.to_string()),
+        ],
+    )
+    .await
+}
+
+// Free and does not use search credits, so it runs before every domain search
+pub async fn query_hunterio_email_count(domain: &str) -> Result<Value, Box<dyn Error>> {
+    let client = reqwest::Client::new();
 
-    let response = client.get(&url).send().await?;
+    let response = client
+        .get("https://api.hunter.io/v2/email-count")
+        .query(&[("domain", domain)])
+        .send()
+        .await?;
     let response_body = response.text().await?;
 
-    Ok(response_body)
+    Ok(serde_json::from_str(&response_body)?)
+}
+
+pub async fn query_hunterio_email_finder(
+    domain: &str,
+    first_name: &str,
+    last_name: &str,
+) -> Result<Value, Box<dyn Error>> {
+    query_hunterio_endpoint(
+        "email-finder",
+        &[("domain", domain), ("first_name", first_name), ("last_name", last_name)],
+    )
+    .await
+}
+
+pub async fn query_hunterio_email_verifier(email: &str) -> Result<Value, Box<dyn Error>> {
+    query_hunterio_endpoint("email-verifier", &[("email", email)]).await
+}
+
+// Pages through domain-search with offset until the limit or the total is reached.
+// Without a limit only the first page is fetched, since every page costs a credit.
+pub async fn collect_hunterio_emails(domain: &str, limit: Option<usize>) -> Result<Value, Box<dyn Error>> {
+    collect_hunterio_pages(limit, |page_size, offset| query_hunterio(domain, page_size, offset)).await
+}
+
+// The paging behind collect_hunterio_emails, with fetch_page(page_size, offset) doing the request.
+// An error on the first page is returned as is; a later one (usually running out of credits)
+// keeps the emails collected so far and is reported under "errors" next to them.
+async fn collect_hunterio_pages<F, Fut>(limit: Option<usize>, mut fetch_page: F) -> Result<Value, Box<dyn Error>>
+where
+    F: FnMut(usize, usize) -> Fut,
+    Fut: Future<Output = Result<Value, Box<dyn Error>>>,
+{
+    let wanted = limit.unwrap_or(HUNTERIO_PAGE_SIZE).max(1);
+    let mut first_page: Option<Value> = None;
+    let mut emails: Vec<Value> = Vec::new();
+    let mut errors: Option<Value> = None;
+
+    loop {
+        let page_size = HUNTERIO_PAGE_SIZE.min(wanted - emails.len());
+        let page = fetch_page(page_size, emails.len()).await?;
+        if page["errors"].is_array() {
+            if first_page.is_none() {
+                return Ok(page);
+            }
+            errors = Some(page["errors"].clone());
+            break;
+        }
+
+        let page_emails = page["data"]["emails"].as_array().cloned().unwrap_or_default();
+        let total = page["meta"]["results"].as_u64().unwrap_or(0) as usize;
+        let page_was_empty = page_emails.is_empty();
+
+        emails.extend(page_emails);
+        if first_page.is_none() {
+            first_page = Some(page);
+        }
+
+        if page_was_empty || emails.len() >= wanted || emails.len() >= total {
+            break;
+        }
+    }
+
+    let mut result = first_page.unwrap_or_else(|| json!({}));
+    result["data"]["emails"] = Value::Array(emails);
+    if let Some(errors) = errors {
+        result["errors"] = errors;
+    }
+    Ok(result)
 }
 
 pub async fn run_single_search_hunterio(
     target: &str,
     output_file: Option<&str>,
+    options: &SearchOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    if is_domain(target) {
-        let hunterio_result = query_hunterio(target).await?;
-        let parsed_result = serde_json::from_str::<Value>(&hunterio_result)?;
-
-        if let Some(output_file) = output_file {
-            let mut file = File::create(output_file)?;
-            writeln!(file, "HunterIO: \n{}", serde_json::to_string_pretty(&parsed_result)?)?;
-        } else {
-            println!("HunterIO: \n{}", serde_json::to_string_pretty(&parsed_result)?);
+    let parsed_result = if is_email(target) {
+        query_hunterio_email_verifier(target).await?
+    } else if is_domain(target) {
+        match (&options.first_name, &options.last_name) {
+            (Some(first_name), Some(last_name)) => {
+                query_hunterio_email_finder(target, first_name, last_name).await?
+            }
+            _ => json!({
+                "email_count": query_hunterio_email_count(target).await?,
+                "domain_search": collect_hunterio_emails(target, options.limit).await?,
+            }),
         }
     } else {
         println!("Invalid target: {}", target);
+        return Ok(());
+    };
+
+    if let Some(output_file) = output_file {
+        let mut file = open_output_file(output_file)?;
+        writeln!(file, "HunterIO: \n{}", serde_json::to_string_pretty(&parsed_result)?)?;
+    } else {
+        println!("HunterIO: \n{}", serde_json::to_string_pretty(&parsed_result)?);
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn email_page(emails: std::ops::Range<usize>, total: usize) -> Value {
+        let emails: Vec<Value> = emails.map(|n| json!({ "value": format!("user{}@example.com", n) })).collect();
+        json!({ "data": { "domain": "example.com", "emails": emails }, "meta": { "results": total } })
+    }
+
+    fn values(result: &Value) -> Vec<String> {
+        result["data"]["emails"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|email| email["value"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn pages_by_offset_up_to_the_limit() {
+        let requests = RefCell::new(Vec::new());
+        let result = collect_hunterio_pages(Some(25), |page_size, offset| {
+            requests.borrow_mut().push((page_size, offset));
+            async move { Ok(email_page(offset..offset + page_size, 100)) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(*requests.borrow(), [(10, 0), (10, 10), (5, 20)]);
+        assert_eq!(values(&result).len(), 25);
+        assert_eq!(result["data"]["domain"], "example.com");
+        assert_eq!(result["meta"]["results"], 100);
+    }
+
+    #[tokio::test]
+    async fn one_page_without_a_limit_and_stops_at_the_total() {
+        let requests = RefCell::new(0);
+        let result = collect_hunterio_pages(None, |page_size, offset| {
+            *requests.borrow_mut() += 1;
+            async move { Ok(email_page(offset..offset + page_size, 100)) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(*requests.borrow(), 1);
+        assert_eq!(values(&result).len(), 10);
+
+        let result = collect_hunterio_pages(Some(50), |_, offset| async move { Ok(email_page(offset..(offset + 10).min(12), 12)) })
+            .await
+            .unwrap();
+        assert_eq!(values(&result).len(), 12);
+    }
+
+    #[tokio::test]
+    async fn first_page_errors_are_returned_as_is() {
+        let error_page = json!({ "errors": [{ "id": "wrong_params", "details": "Invalid domain" }] });
+        let expected = error_page.clone();
+        let result = collect_hunterio_pages(Some(30), move |_, _| {
+            let page = error_page.clone();
+            async move { Ok(page) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[tokio::test]
+    async fn later_errors_keep_the_emails_collected() {
+        let result = collect_hunterio_pages(Some(30), |page_size, offset| async move {
+            if offset == 0 {
+                Ok(email_page(0..page_size, 100))
+            } else {
+                Ok(json!({ "errors": [{ "id": "too_many_requests" }] }))
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(values(&result).len(), 10);
+        assert_eq!(result["errors"][0]["id"], "too_many_requests");
+    }
+}
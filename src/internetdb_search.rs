@@ -1,13 +1,18 @@
 #![allow(unused)]
-use reqwest;
 use std::env;
 use std::error::Error;
 use std::fmt;
 use std::net::IpAddr;
 use regex::Regex;
 use serde_json::Value;
-use std::fs::File;
 use std::io::Write;
+use crate::options::SearchOptions;
+use std::collections::HashMap;
+use std::time::Duration;
+use serde_json::json;
+use reqwest::StatusCode;
+use tokio::task::JoinSet;
+use crate::read_list::open_output_file;
 
 #[derive(Debug)]
 pub struct InternetDBSearchError {
@@ -33,11 +38,7 @@ pub fn is_ip(target: &str) -> bool {
 }
 
 pub async fn query_internetdb(target: &str) -> Result<String, Box<dyn Error>> {
-    let url = if is_ip(target) {
-        format!("https://internetdb.shodan.io/{}", target)
-    } else {
-        format!("https://internetdb.shodan.io/{}", target)
-    };
+    let url = format!("https://internetdb.shodan.io/{}", target);
 
     let client = reqwest::Client::new();
 
@@ -58,15 +59,19 @@ pub async fn query_internetdb(target: &str) -> Result<String, Box<dyn Error>> {
 pub async fn run_single_search_internetdb(
     target: &str,
     output_file: Option<&str>,
+    options: &SearchOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
     if is_ip(target) {
         let internetdb_result = query_internetdb(target).await?;
-        let parsed_result = serde_json::from_str::<Value>(&internetdb_result)?;
+        let mut parsed_result = serde_json::from_str::<Value>(&internetdb_result)?;
+        if let Some(feed) = &options.cve_feed {
+            feed.enrich_field(&mut parsed_result, "vulns", options.min_cvss)?;
+        }
 
         match output_file {
             Some(file_path) => {
-                let mut file = File::create(file_path)?;
-                write!(file, "{}", serde_json::to_string_pretty(&parsed_result)?)?;
+                let mut file = open_output_file(file_path)?;
+                writeln!(file, "InternetDB:\n{}", serde_json::to_string_pretty(&parsed_result)?)?;
             }
             None => {
                 println!("InternetDB:");
@@ -77,4 +82,179 @@ pub async fn run_single_search_internetdb(
         println!("Invalid target: {}", target);
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+// InternetDB answers 404 for addresses it has nothing on, so that is Ok(None) here.
+// 429s are retried with a short backoff since bulk sweeps hit the rate limit.
+pub async fn lookup_internetdb(
+    client: &reqwest::Client,
+    ip: &str,
+) -> Result<Option<Value>, InternetDBSearchError> {
+    let url = format!("https://internetdb.shodan.io/{}", ip);
+    let mut backoff = Duration::from_millis(500);
+
+    for _ in 0..4 {
+        let response = client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|err| InternetDBSearchError::new(err.to_string()))?;
+
+        match response.status() {
+            StatusCode::NOT_FOUND => return Ok(None),
+            StatusCode::TOO_MANY_REQUESTS => {
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            status if status.is_success() => {
+                let parsed_result = response
+                    .json::<Value>()
+                    .await
+                    .map_err(|err| InternetDBSearchError::new(err.to_string()))?;
+                return Ok(Some(parsed_result));
+            }
+            status => return Err(InternetDBSearchError::new(format!("HTTP {}", status))),
+        }
+    }
+
+    Err(InternetDBSearchError::new("rate limited".to_string()))
+}
+
+// Running totals across a sweep, so a /16 can be summarised without keeping every host
+#[derive(Debug, Default)]
+struct SweepSummary {
+    scanned: usize,
+    with_data: usize,
+    failed: usize,
+    hosts_with_vulns: usize,
+    ports: HashMap<String, usize>,
+    tags: HashMap<String, usize>,
+    cpes: HashMap<String, usize>,
+    vulns: HashMap<String, usize>,
+}
+
+impl SweepSummary {
+    fn add(&mut self, host: &Value) {
+        self.with_data += 1;
+        count_values(&mut self.ports, &host["ports"]);
+        count_values(&mut self.tags, &host["tags"]);
+        count_values(&mut self.cpes, &host["cpes"]);
+        count_values(&mut self.vulns, &host["vulns"]);
+        if host["vulns"].as_array().is_some_and(|vulns| !vulns.is_empty()) {
+            self.hosts_with_vulns += 1;
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "scanned": self.scanned,
+            "with_data": self.with_data,
+            "no_data": self.scanned - self.with_data - self.failed,
+            "failed": self.failed,
+            "hosts_with_vulns": self.hosts_with_vulns,
+            "unique_cves": self.vulns.len(),
+            "ports": ranked(&self.ports, "port"),
+            "tags": ranked(&self.tags, "tag"),
+            "cpes": ranked(&self.cpes, "cpe"),
+            "vulns": ranked(&self.vulns, "cve"),
+        })
+    }
+}
+
+fn count_values(counts: &mut HashMap<String, usize>, values: &Value) {
+    for value in values.as_array().into_iter().flatten() {
+        let key = match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        *counts.entry(key).or_insert(0) += 1;
+    }
+}
+
+// Most common first, as [{"port": "443", "hosts": 120}, ...]
+fn ranked(counts: &HashMap<String, usize>, key_name: &str) -> Vec<Value> {
+    let mut entries: Vec<(&String, &usize)> = counts.iter().collect();
+    entries.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    entries
+        .into_iter()
+        .map(|(key, hosts)| json!({ key_name: key, "hosts": hosts }))
+        .collect()
+}
+
+// Keyless, so the only limit on throughput is how hard we hit InternetDB
+pub async fn run_bulk_search_internetdb(
+    targets: Vec<String>,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()?;
+
+    let mut summary = SweepSummary::default();
+    let mut hosts: Vec<Value> = Vec::new();
+    let mut pending = targets.into_iter().filter(|target| {
+        if !is_ip(target) {
+            println!("Invalid target: {}", target);
+        }
+        is_ip(target)
+    });
+    let mut tasks = JoinSet::new();
+
+    let spawn_lookup = |tasks: &mut JoinSet<_>, ip: String| {
+        let client = client.clone();
+        tasks.spawn(async move {
+            let outcome = lookup_internetdb(&client, &ip).await;
+            (ip, outcome)
+        });
+    };
+
+    for ip in pending.by_ref().take(options.concurrency.max(1)) {
+        spawn_lookup(&mut tasks, ip);
+    }
+
+    while let Some(joined) = tasks.join_next().await {
+        let (ip, outcome) = joined?;
+        summary.scanned += 1;
+
+        match outcome {
+            Ok(Some(mut host)) => {
+                summary.add(&host);
+                if !options.summary {
+                    if let Some(feed) = &options.cve_feed {
+                        feed.enrich_field(&mut host, "vulns", options.min_cvss)?;
+                    }
+                    hosts.push(host);
+                }
+            }
+            Ok(None) => {}
+            Err(err) => {
+                summary.failed += 1;
+                println!("InternetDB lookup failed for {}: {}", ip, err);
+            }
+        }
+
+        if let Some(ip) = pending.next() {
+            spawn_lookup(&mut tasks, ip);
+        }
+    }
+
+    let result = if options.summary {
+        json!({ "summary": summary.to_json() })
+    } else {
+        hosts.sort_by(|a, b| a["ip"].as_str().cmp(&b["ip"].as_str()));
+        json!({ "summary": summary.to_json(), "hosts": hosts })
+    };
+
+    match output_file {
+        Some(file_path) => {
+            let mut file = open_output_file(file_path)?;
+            writeln!(file, "InternetDB:\n{}", serde_json::to_string_pretty(&result)?)?;
+        }
+        None => {
+            println!("InternetDB:");
+            println!("{}", serde_json::to_string_pretty(&result)?);
+        }
+    }
+    Ok(())
+}
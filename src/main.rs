@@ -12,62 +12,313 @@ mod internetdb_search;
 mod banner;
 mod read_list;
 mod vt_search;
+mod model;
+mod options;
+mod cve_feed;
+mod target;
+mod error;
+mod urlscan_search;
+mod crtsh_search;
+mod securitytrails_search;
+mod otx_search;
+mod greynoise_search;
+mod abuseipdb_search;
+mod binaryedge_search;
+mod rdap_search;
+mod dns_search;
+mod wayback_search;
 
 use clap::{App, Arg};
 use std::io::Write;
-use regex::Regex;
-use shodan_search::run_single_search_shodan;
-use censys_search::run_single_search_censys;
+use shodan_search::{run_network_search_shodan, run_single_search_shodan};
+use censys_search::{run_network_search_censys, run_single_search_censys};
 use fullhunt_search::run_single_search_fullhunt;
 use hunterio_search::run_single_search_hunterio;
 use projectdiscovery_search::run_single_search_projectdiscovery;
 use criminalip_search::run_single_search_criminalip;
-use netlas_search::run_single_search_netlas;
-use zoomeye_search::run_single_search_zoomeye;
-use internetdb_search::run_single_search_internetdb;
+use netlas_search::{run_network_search_netlas, run_query_search_netlas, run_single_search_netlas};
+use zoomeye_search::{run_network_search_zoomeye, run_query_search_zoomeye, run_single_search_zoomeye};
+use internetdb_search::{run_bulk_search_internetdb, run_single_search_internetdb};
 use banner::display_banner;
-use read_list::read_targets_from_file;
-use vt_search::run_single_search_virustotal;
+use read_list::{expand_target, read_asn_prefixes, read_targets_from_file};
+use std::fs::File;
+use vt_search::{run_single_search_virustotal, search_virustotal, write_virustotal_report};
+use urlscan_search::run_single_search_urlscan;
+use crtsh_search::run_single_search_crtsh;
+use securitytrails_search::run_single_search_securitytrails;
+use otx_search::run_single_search_otx;
+use greynoise_search::run_single_search_greynoise;
+use abuseipdb_search::run_single_search_abuseipdb;
+use binaryedge_search::run_single_search_binaryedge;
+use rdap_search::run_single_search_rdap;
+use dns_search::{parse_resolver, run_single_search_dns};
+use wayback_search::run_single_search_wayback;
+use options::SearchOptions;
+use target::{Network, Target};
 
 async fn run_all_searches(
     search_types: Vec<&str>,
     target: &str,
     output_file: Option<&str>,
+    options: &SearchOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let re = Regex::new(r"^[a-zA-Z0-9][a-zA-Z0-9-]{1,61}[a-zA-Z0-9]\.[a-zA-Z]{2,}$").unwrap();
-
-    if let Ok(ip) = target.parse::<std::net::IpAddr>() {
-        for search_type in &search_types {
-            match *search_type {
-                "shodan" => run_single_search_shodan(&ip.to_string(), output_file).await?,
-                "censys" => run_single_search_censys(&ip.to_string(), output_file).await?,
-                "criminalip" => run_single_search_criminalip(&ip.to_string(), output_file).await?,
-                "netlas" => run_single_search_netlas(&ip.to_string(), output_file).await?,
-                "zoomeye" => run_single_search_zoomeye(&ip.to_string(), output_file).await?,
-                "internetdb" => run_single_search_internetdb(&ip.to_string(), output_file).await?,
-                "virustotal" => run_single_search_virustotal(&ip.to_string(), output_file).await?,
-                _ => println!("Invalid search type for IP: {}", search_type),
+    match Target::parse(target) {
+        Some(Target::Ip(ip)) => run_ip_searches(&search_types, &ip.to_string(), output_file, options).await?,
+        Some(Target::Domain(domain)) => {
+            let resolved_ips = run_domain_searches(&search_types, &domain, output_file, options).await?;
+            // Only the user's own domain feeds its addresses back, so hash and email pivots
+            // can't fan out into every IP provider for every address they resolve to
+            for ip in &resolved_ips {
+                println!("Resolved IP pivot: {} (from {})", ip, domain);
+                run_ip_searches(&search_types, ip, output_file, &options.for_pivot()).await?;
             }
         }
-    } else if re.is_match(target) {
-        for search_type in &search_types {
-            match *search_type {
-                "shodan" => run_single_search_shodan(target, output_file).await?,
-                "censys" => run_single_search_censys(target, output_file).await?,
-                "fullhunt" => run_single_search_fullhunt(target, output_file).await?,
-                "projectdiscovery" => run_single_search_projectdiscovery(target, output_file).await?,
-                "hunterio" => run_single_search_hunterio(target, output_file).await?,
-                "netlas" => run_single_search_netlas(target, output_file).await?,
-                _ => println!("Invalid search type for domain: {}", search_type),
+        Some(Target::Email { address, domain }) => {
+            run_email_searches(&search_types, &address, output_file, options).await?;
+            // Search the owning domain too, keeping the link back to the email
+            println!("Domain pivot: {} (from {})", domain, address);
+            run_domain_searches(&search_types, &domain, output_file, &options.for_pivot()).await?;
+        }
+        Some(Target::Hash(hash)) => run_hash_searches(&search_types, &hash, output_file, options).await?,
+        Some(Target::Url { url, host }) => {
+            run_url_searches(&search_types, &url, output_file, options).await?;
+            // The host goes through the usual providers, tied back to the URL it came from
+            println!("Host pivot: {} (from {})", host, url);
+            // Only the URL itself is submitted to urlscan.io, not its host as well
+            let pivot_options = options.for_pivot();
+            if host.parse::<std::net::IpAddr>().is_ok() {
+                run_ip_searches(&search_types, &host, output_file, &pivot_options).await?;
+            } else {
+                run_domain_searches(&search_types, &host, output_file, &pivot_options).await?;
+            }
+        }
+        Some(Target::Network(network)) => run_network_searches(&search_types, &network, output_file, options).await?,
+        Some(Target::Cidr(cidr)) => run_cidr_searches(&search_types, &cidr, output_file, options).await?,
+        None => println!("Invalid target: {}", target),
+    }
+
+    Ok(())
+}
+
+async fn run_ip_searches(
+    search_types: &[&str],
+    ip: &str,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for search_type in search_types {
+        match *search_type {
+            "shodan" => run_single_search_shodan(ip, output_file, options).await?,
+            "censys" => run_single_search_censys(ip, output_file).await?,
+            "criminalip" => run_single_search_criminalip(ip, output_file, options).await?,
+            "netlas" => run_single_search_netlas(ip, output_file).await?,
+            "zoomeye" => run_single_search_zoomeye(ip, output_file, options).await?,
+            "internetdb" => run_single_search_internetdb(ip, output_file, options).await?,
+            "virustotal" => run_single_search_virustotal(ip, output_file, options).await?,
+            "securitytrails" => run_single_search_securitytrails(ip, output_file, options).await?,
+            "otx" => run_single_search_otx(ip, output_file, options).await?,
+            "greynoise" => run_single_search_greynoise(ip, output_file, options).await?,
+            "abuseipdb" => run_single_search_abuseipdb(ip, output_file, options).await?,
+            "urlscan" => run_single_search_urlscan(ip, output_file, options).await?,
+            "binaryedge" => run_single_search_binaryedge(ip, output_file, options).await?,
+            "rdap" => run_single_search_rdap(ip, output_file, options).await?,
+            _ if options.is_pivot => {}
+            _ => println!("Invalid search type for IP: {}", search_type),
+        }
+    }
+
+    Ok(())
+}
+
+// Returns the addresses found by the dns provider, if it ran
+async fn run_domain_searches(
+    search_types: &[&str],
+    domain: &str,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut resolved_ips: Vec<String> = Vec::new();
+
+    for search_type in search_types {
+        match *search_type {
+            "shodan" => run_single_search_shodan(domain, output_file, options).await?,
+            "censys" => run_single_search_censys(domain, output_file).await?,
+            "fullhunt" => run_single_search_fullhunt(domain, output_file, options).await?,
+            "projectdiscovery" => run_single_search_projectdiscovery(domain, output_file, options).await?,
+            "hunterio" => run_single_search_hunterio(domain, output_file, options).await?,
+            "netlas" => run_single_search_netlas(domain, output_file).await?,
+            "zoomeye" => run_single_search_zoomeye(domain, output_file, options).await?,
+            "virustotal" => run_single_search_virustotal(domain, output_file, options).await?,
+            "crtsh" => run_single_search_crtsh(domain, output_file, options).await?,
+            "securitytrails" => run_single_search_securitytrails(domain, output_file, options).await?,
+            "otx" => run_single_search_otx(domain, output_file, options).await?,
+            "urlscan" => run_single_search_urlscan(domain, output_file, options).await?,
+            "binaryedge" => run_single_search_binaryedge(domain, output_file, options).await?,
+            "rdap" => run_single_search_rdap(domain, output_file, options).await?,
+            "dns" => resolved_ips = run_single_search_dns(domain, output_file, options).await?,
+            "wayback" => run_single_search_wayback(domain, output_file, options).await?,
+            _ if options.is_pivot => {}
+            _ => println!("Invalid search type for domain: {}", search_type),
+        }
+    }
+
+    Ok(resolved_ips)
+}
+
+async fn run_email_searches(
+    search_types: &[&str],
+    email: &str,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Other providers still run against the domain pivot, which goes ahead even if the
+    // verifier fails
+    if search_types.contains(&"hunterio") {
+        if let Err(err) = run_single_search_hunterio(email, output_file, options).await {
+            println!("hunterio search failed for {}: {}", email, err);
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_network_searches(
+    search_types: &[&str],
+    network: &Network,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let prefixes = match (network, &options.prefix_file) {
+        (Network::Asn(asn), Some(prefix_file)) => {
+            let prefixes = read_asn_prefixes(prefix_file, *asn)?;
+            println!("Prefixes announced by AS{}:", asn);
+            println!("{}", serde_json::to_string_pretty(&prefixes)?);
+            prefixes
+        }
+        _ => Vec::new(),
+    };
+
+    for search_type in search_types {
+        match *search_type {
+            "shodan" => run_network_search_shodan(network, output_file, options).await?,
+            "censys" => run_network_search_censys(network, output_file, options).await?,
+            "netlas" => run_network_search_netlas(network, output_file, options).await?,
+            "zoomeye" => run_network_search_zoomeye(network, output_file, options).await?,
+            // AbuseIPDB has no ASN search, so each announced prefix goes to check-block instead
+            "abuseipdb" if !prefixes.is_empty() => {
+                for prefix in &prefixes {
+                    if let Err(err) = run_single_search_abuseipdb(prefix, output_file, options).await {
+                        println!("AbuseIPDB check-block failed for {}: {}", prefix, err);
+                    }
+                }
             }
+            _ => println!("Invalid search type for ASN or organisation: {}", search_type),
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_cidr_searches(
+    search_types: &[&str],
+    cidr: &str,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for search_type in search_types {
+        match *search_type {
+            "abuseipdb" => run_single_search_abuseipdb(cidr, output_file, options).await?,
+            "internetdb" => println!("Use --internetdb-bulk to sweep a CIDR through InternetDB: {}", cidr),
+            _ => println!("Invalid search type for CIDR: {}", search_type),
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_url_searches(
+    search_types: &[&str],
+    url: &str,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for search_type in search_types {
+        let result = match *search_type {
+            "virustotal" => run_single_search_virustotal(url, output_file, options).await,
+            "urlscan" => run_single_search_urlscan(url, output_file, options).await,
+            // Other providers only see the host pivot
+            _ => Ok(()),
+        };
+        // A new phishing URL is often unknown to the provider; the host pivot still runs
+        if let Err(err) = result {
+            println!("{} search failed for {}: {}", search_type, url, err);
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_hash_searches(
+    search_types: &[&str],
+    hash: &str,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if search_types.contains(&"otx") {
+        run_single_search_otx(hash, output_file, options).await?;
+    }
+
+    if !search_types.contains(&"virustotal") {
+        if !search_types.contains(&"otx") {
+            println!("File hash targets need the virustotal or otx search type: {}", hash);
+        }
+        return Ok(());
+    }
+
+    let report = search_virustotal(hash, options).await?;
+    write_virustotal_report(&report, output_file, options)?;
+
+    // Hosts the sample talked to are offered to the other providers, and searched with --pivot
+    let pivots = report.pivot_targets();
+    if pivots.is_empty() {
+        return Ok(());
+    }
+
+    let pivot_list: Vec<&str> = pivots.iter().map(|pivot| pivot.value.as_str()).collect();
+    println!("Pivot targets from {}: {}", hash, pivot_list.join(", "));
+    if !options.pivot {
+        return Ok(());
+    }
+
+    let pivot_options = options.for_pivot();
+    for pivot in pivots {
+        println!("Pivot: {} ({} of {})", pivot.value, pivot.relation, hash);
+        if pivot.kind == "ip" {
+            run_ip_searches(search_types, &pivot.value, output_file, &pivot_options).await?;
+        } else {
+            run_domain_searches(search_types, &pivot.value, output_file, &pivot_options).await?;
         }
-    } else {
-        println!("Invalid target: {}", target);
     }
 
     Ok(())
 }
 
+async fn run_query_searches(
+    search_types: Vec<&str>,
+    query: &str,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for search_type in &search_types {
+        match *search_type {
+            "netlas" => run_query_search_netlas(query, output_file, options).await?,
+            "zoomeye" => run_query_search_zoomeye(query, output_file, options).await?,
+            _ => println!("Search type does not support --query: {}", search_type),
+        }
+    }
+
+    Ok(())
+}
 
 #[tokio::main]
 async fn main() {
@@ -79,7 +330,7 @@ async fn main() {
             Arg::new("search_type")
                 .long("search_type")
                 .value_name("SEARCH_TYPE")
-                .possible_values(&[
+                .possible_values([
                     "shodan",
                     "censys",
                     "fullhunt",
@@ -90,6 +341,16 @@ async fn main() {
                     "zoomeye",
                     "internetdb",
                     "virustotal",
+                    "urlscan",
+                    "crtsh",
+                    "securitytrails",
+                    "otx",
+                    "greynoise",
+                    "abuseipdb",
+                    "binaryedge",
+                    "rdap",
+                    "dns",
+                    "wayback",
                 ])
                 .help("The type(s) of search, separated by commas")
                 .takes_value(true)
@@ -101,9 +362,9 @@ async fn main() {
             Arg::new("target")
                 .long("target")
                 .value_name("TARGET")
-                .help("The target IP address or domain")
+                .help("The target IP address, CIDR, domain, URL, email address, file hash or ASN (AS13335)")
                 .takes_value(true)
-                .required_unless("target_list"),
+                .required_unless_present_any(["target_list", "query", "org"]),
         )
         .arg(
             Arg::new("target_list")
@@ -112,16 +373,188 @@ async fn main() {
             .value_name("TARGET_LIST")
             .help("List of target IP addresses")
             .takes_value(true)
-            .required_unless("target"),
+            .required_unless_present_any(["target", "query", "org"]),
+        )
+        .arg(
+            Arg::new("org")
+                .long("org")
+                .value_name("ORG")
+                .help("Enumerate hosts belonging to an organisation (shodan, censys, netlas, zoomeye)")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("prefix_file")
+                .long("prefix-file")
+                .value_name("FILE")
+                .help("Local prefix-to-AS dump (CAIDA pfx2as or \"prefix asn\" lines) used to expand ASN targets")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("query")
+                .long("query")
+                .value_name("QUERY")
+                .help("Run a provider search query instead of looking up a target (netlas, zoomeye)")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("netlas_type")
+                .long("netlas-type")
+                .value_name("NETLAS_TYPE")
+                .possible_values(["responses", "domains", "whois-ip", "whois-domain"])
+                .default_value("responses")
+                .help("The Netlas collection searched by --query")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("lang")
+                .long("lang")
+                .value_name("LANG")
+                .default_value("en")
+                .help("Language used for localized result fields such as ZoomEye geo names")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("limit")
+                .long("limit")
+                .value_name("LIMIT")
+                .help("Maximum number of results to fetch from paged searches")
+                .takes_value(true)
+                .validator(|limit| limit.parse::<usize>()),
+        )
+        .arg(
+            Arg::new("nvd_feed")
+                .long("nvd-feed")
+                .value_name("FILE")
+                .help("Local NVD JSON feed used to enrich CVE IDs (repeatable)")
+                .takes_value(true)
+                .multiple_occurrences(true),
+        )
+        .arg(
+            Arg::new("kev_feed")
+                .long("kev-feed")
+                .value_name("FILE")
+                .help("Local CISA KEV catalog used to flag known exploited CVEs")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("min_cvss")
+                .long("min-cvss")
+                .value_name("SCORE")
+                .help("Only report enriched CVEs with at least this CVSS score")
+                .takes_value(true)
+                .requires("nvd_feed")
+                .validator(|score| score.parse::<f64>()),
         )
         .arg(
             Arg::new("output")
                 .short('o')
                 .long("output")
                 .value_name("FILE")
-                .help("Output the results to a file")
+                .help("Output the results to a file; every search type and target is appended to it under the provider's name")
                 .takes_value(true),
         )
+        .arg(
+            Arg::new("internetdb_bulk")
+                .long("internetdb-bulk")
+                .help("Sweep the target, target list or IPv4 CIDR (up to /16) through InternetDB concurrently and aggregate the results"),
+        )
+        .arg(
+            Arg::new("concurrency")
+                .long("concurrency")
+                .value_name("N")
+                .default_value("50")
+                .help("Number of concurrent requests for --internetdb-bulk")
+                .takes_value(true)
+                .validator(|n| n.parse::<usize>()),
+        )
+        .arg(
+            Arg::new("no_chaos")
+                .long("no-chaos")
+                .help("Query the ProjectDiscovery API directly even if the chaos client is installed"),
+        )
+        .arg(
+            Arg::new("fullhunt_host")
+                .long("fullhunt-host")
+                .help("Treat the target as a single host and fetch its FullHunt host details"),
+        )
+        .arg(
+            Arg::new("first_name")
+                .long("first-name")
+                .value_name("FIRST_NAME")
+                .help("First name for a Hunter.io email finder lookup on the target domain")
+                .takes_value(true)
+                .requires("last_name"),
+        )
+        .arg(
+            Arg::new("last_name")
+                .long("last-name")
+                .value_name("LAST_NAME")
+                .help("Last name for a Hunter.io email finder lookup on the target domain")
+                .takes_value(true)
+                .requires("first_name"),
+        )
+        .arg(
+            Arg::new("vt_relations")
+                .long("vt-relations")
+                .value_name("RELATIONS")
+                .possible_values(["resolutions", "subdomains", "communicating_files", "referrer_files"])
+                .help("VirusTotal relationships to page through for IP and domain targets, separated by commas")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .use_delimiter(true)
+                .value_delimiter(','),
+        )
+        .arg(
+            Arg::new("max_age_days")
+                .long("max-age-days")
+                .value_name("DAYS")
+                .default_value("90")
+                .help("Only count AbuseIPDB reports from the last DAYS days (1-365)")
+                .takes_value(true)
+                .validator(|days| match days.parse::<u32>() {
+                    Ok(1..=365) => Ok(()),
+                    _ => Err("must be a number of days between 1 and 365"),
+                }),
+        )
+        .arg(
+            Arg::new("urlscan_submit")
+                .long("urlscan-submit")
+                .help("Submit the URL or domain target to urlscan.io for a new scan (sends the target to a third party)"),
+        )
+        .arg(
+            Arg::new("urlscan_visibility")
+                .long("urlscan-visibility")
+                .value_name("VISIBILITY")
+                .possible_values(["public", "unlisted", "private"])
+                .default_value("private")
+                .help("Visibility of scans submitted with --urlscan-submit")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("rdap_bootstrap")
+                .long("rdap-bootstrap")
+                .value_name("DIR")
+                .help("Directory caching IANA's RDAP bootstrap files; they are downloaded into it when missing")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("resolver")
+                .long("resolver")
+                .value_name("IP[:PORT]")
+                .help("DNS server used by the dns provider instead of the system resolver")
+                .takes_value(true)
+                .validator(|resolver| parse_resolver(resolver).ok_or("must be an IP address, optionally with a port")),
+        )
+        .arg(
+            Arg::new("pivot")
+                .long("pivot")
+                .help("Also search the IPs and domains related to a file hash target"),
+        )
+        .arg(
+            Arg::new("summary")
+                .long("summary")
+                .help("Print a compact verdict per target instead of the full result"),
+        )
         .arg(
             Arg::new("all")
                 .short('a')
@@ -132,13 +565,79 @@ async fn main() {
 
         let search_types: Vec<&str> = matches.values_of("search_type").unwrap_or_default().collect();
         let output_file = matches.value_of("output");
+        let options = match SearchOptions::from_matches(&matches) {
+            Ok(options) => options,
+            Err(err) => {
+                println!("{}", err);
+                return;
+            }
+        };
+
+        if let Some(output_file) = output_file {
+            if let Err(err) = File::create(output_file) {
+                println!("Failed to create output file {}: {}", output_file, err);
+                return;
+            }
+        }
     
-        if let Some(target_list_path) = matches.value_of("target_list") {
+        if matches.is_present("internetdb_bulk") {
+            let raw_targets = match matches.value_of("target_list") {
+                Some(target_list_path) => match read_targets_from_file(target_list_path) {
+                    Ok(targets) => targets,
+                    Err(e) => {
+                        println!("Failed to read target list: {}", e);
+                        return;
+                    }
+                },
+                None => matches.value_of("target").map(String::from).into_iter().collect(),
+            };
+
+            let mut targets = Vec::new();
+            for target in raw_targets.iter().map(|target| target.trim()).filter(|target| !target.is_empty()) {
+                // ASN targets are swept across their announced prefixes when a dump is given
+                let ranges = match (Target::parse(target), &options.prefix_file) {
+                    (Some(Target::Network(Network::Asn(asn))), Some(prefix_file)) => {
+                        match read_asn_prefixes(prefix_file, asn) {
+                            Ok(prefixes) => prefixes,
+                            Err(err) => {
+                                println!("Failed to read prefix file: {}", err);
+                                return;
+                            }
+                        }
+                    }
+                    _ => vec![target.to_string()],
+                };
+
+                for range in ranges {
+                    match expand_target(&range) {
+                        Ok(expanded) => targets.extend(expanded),
+                        Err(err) => println!("Skipping {}", err),
+                    }
+                }
+            }
+
+            if let Err(err) = run_bulk_search_internetdb(targets, output_file, &options).await {
+                println!("Error while running InternetDB sweep: {}", err);
+            }
+        } else if let Some(org) = matches.value_of("org") {
+            let network = Network::Org(org.to_string());
+            if search_types.is_empty() {
+                println!("Please specify the search type(s) to enumerate the organisation with.");
+            } else if let Err(err) = run_network_searches(&search_types, &network, output_file, &options).await {
+                println!("Error while running organisation searches: {}", err);
+            }
+        } else if let Some(query) = matches.value_of("query") {
+            if search_types.is_empty() {
+                println!("Please specify the search type(s) to run the query against.");
+            } else if let Err(err) = run_query_searches(search_types.clone(), query, output_file, &options).await {
+                println!("Error while running query: {}", err);
+            }
+        } else if let Some(target_list_path) = matches.value_of("target_list") {
             // Read targets from file
             match read_targets_from_file(target_list_path) {
                 Ok(targets) => {
                     for target in targets {
-                        if let Err(err) = run_all_searches(search_types.clone(), &target, output_file).await {
+                        if let Err(err) = run_all_searches(search_types.clone(), &target, output_file, &options).await {
                             println!("Error while running searches for target {}: {}", target, err);
                         }
                     }
@@ -148,7 +647,7 @@ async fn main() {
         } else if let Some(single_target) = matches.value_of("target") {
             // Code for handling single target
             if !search_types.is_empty() {
-                if let Err(err) = run_all_searches(search_types.clone(), single_target, output_file).await {
+                if let Err(err) = run_all_searches(search_types.clone(), single_target, output_file, &options).await {
                     println!("Error while running specified searches: {}", err);
                 }
             } else if matches.is_present("all") {
@@ -163,8 +662,18 @@ async fn main() {
                     "zoomeye",
                     "internetdb",
                     "virustotal",
+                    "urlscan",
+                    "crtsh",
+                    "securitytrails",
+                    "otx",
+                    "greynoise",
+                    "abuseipdb",
+                    "binaryedge",
+                    "rdap",
+                    "dns",
+                    "wayback",
                 ];
-                if let Err(err) = run_all_searches(all_search_types, single_target, output_file).await {
+                if let Err(err) = run_all_searches(all_search_types, single_target, output_file, &options).await {
                     println!("Error while running all searches: {}", err);
                 }
             } else {
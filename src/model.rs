@@ -0,0 +1,441 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Provider-independent view of a single host, so results from different sources line up
+#[derive(Debug, Default, Serialize)]
+pub struct HostRecord {
+    pub source: String,
+    // Missing for FullHunt hosts that don't resolve
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hostnames: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub risk: Option<RiskVerdict>,
+    pub services: Vec<Service>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub technologies: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    pub vulnerabilities: Vec<Vulnerability>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct RiskVerdict {
+    pub verdict: String,
+    pub inbound: Option<String>,
+    pub outbound: Option<String>,
+    pub flags: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct Service {
+    pub port: u16,
+    pub protocol: Option<String>,
+    pub product: Option<String>,
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Vulnerability {
+    pub id: String,
+    pub cvss: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+    pub summary: Option<String>,
+    pub known_exploited: bool,
+    // The product the CVE was matched against, for providers that report it (BinaryEdge)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpe: Option<String>,
+}
+
+// Subdomains found for a domain by one enumeration source
+#[derive(Debug, Default, Serialize)]
+pub struct SubdomainRecord {
+    pub source: String,
+    pub domain: String,
+    pub subdomains: Vec<String>,
+}
+
+// A certificate seen in certificate transparency logs
+#[derive(Debug, Default, Serialize)]
+pub struct CertificateRecord {
+    pub id: u64,
+    pub common_name: Option<String>,
+    pub issuer: Option<String>,
+    pub not_before: Option<String>,
+    pub not_after: Option<String>,
+}
+
+// One web page scan, e.g. a urlscan.io result
+#[derive(Debug, Default, Serialize)]
+pub struct WebScanRecord {
+    pub source: String,
+    pub uuid: String,
+    pub url: Option<String>,
+    pub final_url: Option<String>,
+    pub title: Option<String>,
+    pub server: Option<String>,
+    pub ip: Option<String>,
+    pub asn: Option<String>,
+    pub asn_name: Option<String>,
+    pub scanned_at: Option<String>,
+    pub screenshot: Option<String>,
+    pub redirects: Vec<String>,
+    pub contacted_domains: Vec<String>,
+}
+
+// Registration and ownership of an IP block or domain, e.g. from RDAP
+#[derive(Debug, Default, Serialize)]
+pub struct RegistrationRecord {
+    pub source: String,
+    pub target: String,
+    pub kind: String,
+    // The registry or RIR server that answered
+    pub server: String,
+    pub handle: Option<String>,
+    pub name: Option<String>,
+    pub registrant_org: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registrar: Option<String>,
+    pub abuse_email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub abuse_phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub cidrs: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    pub registered: Option<String>,
+    pub last_changed: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub status: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub nameservers: Vec<String>,
+}
+
+impl RegistrationRecord {
+    // One line per target for --summary
+    pub fn summary_line(&self) -> String {
+        let mut line = format!("{} ({})", self.target, self.kind);
+
+        let fields = [
+            ("org", &self.registrant_org),
+            ("registrar", &self.registrar),
+            ("range", &self.range),
+            ("abuse", &self.abuse_email),
+            ("registered", &self.registered),
+            ("expires", &self.expires),
+        ];
+        for (label, value) in fields {
+            if let Some(value) = value {
+                line.push_str(&format!("  {}={}", label, value));
+            }
+        }
+
+        line
+    }
+}
+
+// Live DNS records for a domain, with mail policy and takeover checks
+#[derive(Debug, Default, Serialize)]
+pub struct DnsReport {
+    pub source: String,
+    pub domain: String,
+    pub resolver: String,
+    // Record type (A, MX, TXT, ...) to the values returned
+    pub records: BTreeMap<String, Vec<String>>,
+    pub spf: Option<String>,
+    pub dmarc: Option<String>,
+    pub dmarc_policy: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dangling_cnames: Vec<String>,
+    pub resolved_ips: Vec<String>,
+    // Record types whose lookup failed, with the resolver error
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub errors: BTreeMap<String, String>,
+}
+
+impl DnsReport {
+    // One line per domain for --summary
+    pub fn summary_line(&self) -> String {
+        let mut line = format!("{}  ips={}", self.domain, self.resolved_ips.join(","));
+
+        line.push_str(if self.spf.is_some() { "  spf" } else { "  no-spf" });
+        match &self.dmarc_policy {
+            Some(policy) => line.push_str(&format!("  dmarc={}", policy)),
+            None => line.push_str("  no-dmarc"),
+        }
+        if !self.dangling_cnames.is_empty() {
+            line.push_str(&format!("  dangling={}", self.dangling_cnames.join(",")));
+        }
+        if !self.errors.is_empty() {
+            let failed: Vec<&str> = self.errors.keys().map(String::as_str).collect();
+            line.push_str(&format!("  failed={}", failed.join(",")));
+        }
+
+        line
+    }
+}
+
+// URLs archived for a domain and its subdomains, e.g. by the Wayback Machine
+#[derive(Debug, Default, Serialize)]
+pub struct UrlHistoryRecord {
+    pub source: String,
+    pub domain: String,
+    pub hosts: BTreeSet<String>,
+    // Query parameter or file extension to the number of URLs using it
+    pub parameters: BTreeMap<String, usize>,
+    pub extensions: BTreeMap<String, usize>,
+    pub urls: Vec<String>,
+}
+
+impl UrlHistoryRecord {
+    // One line per domain for --summary
+    pub fn summary_line(&self) -> String {
+        let mut extensions: Vec<(&String, &usize)> = self.extensions.iter().collect();
+        extensions.sort_by(|a, b| b.1.cmp(a.1));
+        let top: Vec<&str> = extensions.iter().take(5).map(|(ext, _)| ext.as_str()).collect();
+
+        format!(
+            "{}  urls={} hosts={} params={}  top extensions={}",
+            self.domain,
+            self.urls.len(),
+            self.hosts.len(),
+            self.parameters.len(),
+            top.join(",")
+        )
+    }
+}
+
+impl SubdomainRecord {
+    // Accepts bare labels ("www") or full names ("www.example.com", "*.example.com"),
+    // returning lowercase FQDNs under the domain, sorted and deduplicated. Full names
+    // outside the domain are dropped rather than turned into made-up hostnames.
+    pub fn new<I, S>(source: &str, domain: &str, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let domain = domain.trim_end_matches('.').to_lowercase();
+        let suffix = format!(".{}", domain);
+
+        let mut subdomains: Vec<String> = names
+            .into_iter()
+            .map(|name| name.as_ref().trim().trim_end_matches('.').to_lowercase())
+            .map(|name| name.trim_start_matches("*.").to_string())
+            .filter(|name| !name.is_empty() && !name.contains(char::is_whitespace))
+            .filter_map(|name| {
+                if name == domain || name.ends_with(&suffix) {
+                    Some(name)
+                } else if !name.contains('.') {
+                    Some(format!("{}{}", name, suffix))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        subdomains.sort();
+        subdomains.dedup();
+
+        Self {
+            source: source.to_string(),
+            domain,
+            subdomains,
+        }
+    }
+}
+
+// Reputation and relationships of a single indicator (IP, domain, URL or file hash)
+#[derive(Debug, Default, Serialize)]
+pub struct IndicatorReport {
+    pub source: String,
+    pub indicator: String,
+    pub kind: String,
+    // Provider verdict such as benign, malicious or unknown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub classification: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reputation: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_analysis_stats: Option<AnalysisStats>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub categories: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threat_intel: Option<ThreatIntel>,
+    // Provider specific attributes worth keeping, e.g. as_owner or registrar
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    pub details: Map<String, Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<RelatedIndicator>,
+}
+
+// Community threat intelligence about an indicator, e.g. the OTX pulses it appears in
+#[derive(Debug, Default, Serialize)]
+pub struct ThreatIntel {
+    pub pulse_count: u64,
+    pub pulses: Vec<String>,
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub malware_families: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub adversaries: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct AnalysisStats {
+    pub malicious: u64,
+    pub suspicious: u64,
+    pub harmless: u64,
+    pub undetected: u64,
+    pub timeout: u64,
+}
+
+// Another indicator linked to the report, e.g. a domain resolving to the IP
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct RelatedIndicator {
+    pub relation: String,
+    pub kind: String,
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+}
+
+impl IndicatorReport {
+    pub fn new(source: &str, indicator: &str, kind: &str) -> Self {
+        Self {
+            source: source.to_string(),
+            indicator: indicator.to_string(),
+            kind: kind.to_string(),
+            ..Default::default()
+        }
+    }
+
+    // Related IPs and domains, deduplicated, that other providers can be pointed at
+    pub fn pivot_targets(&self) -> Vec<&RelatedIndicator> {
+        let mut pivots: Vec<&RelatedIndicator> = Vec::new();
+        for related in self.related.iter().filter(|r| r.kind == "ip" || r.kind == "domain") {
+            if !pivots.iter().any(|p| p.value == related.value) {
+                pivots.push(related);
+            }
+        }
+        pivots
+    }
+
+    // One line per indicator for --summary
+    pub fn summary_line(&self) -> String {
+        let mut line = format!("{} ({})", self.indicator, self.kind);
+
+        if let Some(classification) = &self.classification {
+            line.push_str(&format!("  {}", classification));
+        }
+
+        if let Some(stats) = &self.last_analysis_stats {
+            let total = stats.malicious + stats.suspicious + stats.harmless + stats.undetected;
+            line.push_str(&format!(
+                "  malicious={}/{} suspicious={}",
+                stats.malicious, total, stats.suspicious
+            ));
+        }
+        if let Some(reputation) = self.reputation {
+            line.push_str(&format!("  reputation={}", reputation));
+        }
+        if let Some(intel) = &self.threat_intel {
+            line.push_str(&format!("  pulses={}", intel.pulse_count));
+            if !intel.malware_families.is_empty() {
+                line.push_str(&format!("  malware={}", intel.malware_families.join(",")));
+            }
+        }
+        if !self.related.is_empty() {
+            line.push_str(&format!("  related={}", self.related.len()));
+        }
+
+        line
+    }
+}
+
+impl HostRecord {
+    pub fn new(source: &str, ip: &str) -> Self {
+        Self {
+            source: source.to_string(),
+            ip: Some(ip.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn max_cvss(&self) -> Option<f64> {
+        self.vulnerabilities
+            .iter()
+            .filter_map(|vuln| vuln.cvss)
+            .fold(None, |max, score| Some(max.map_or(score, |m: f64| m.max(score))))
+    }
+
+    // One line per host for --summary
+    pub fn summary_line(&self) -> String {
+        let mut line = self
+            .ip
+            .clone()
+            .or_else(|| self.hostnames.first().cloned())
+            .unwrap_or_else(|| "-".to_string());
+
+        if let Some(risk) = &self.risk {
+            line.push_str(&format!(
+                "  {}  inbound={} outbound={}",
+                risk.verdict,
+                risk.inbound.as_deref().unwrap_or("-"),
+                risk.outbound.as_deref().unwrap_or("-"),
+            ));
+            if !risk.flags.is_empty() {
+                line.push_str(&format!("  flags={}", risk.flags.join(",")));
+            }
+        }
+
+        line.push_str(&format!("  ports={}", self.services.len()));
+        line.push_str(&format!("  vulns={}", self.vulnerabilities.len()));
+        if let Some(max) = self.max_cvss() {
+            line.push_str(&format!(" (max CVSS {:.1})", max));
+        }
+
+        line
+    }
+}
+
+// Providers are inconsistent about numbers vs strings, so accept either
+pub fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subdomain_record_qualifies_bare_labels() {
+        let record = SubdomainRecord::new("test", "Example.com.", ["www", "API", "www"]);
+        assert_eq!(record.domain, "example.com");
+        assert_eq!(record.subdomains, ["api.example.com", "www.example.com"]);
+    }
+
+    #[test]
+    fn subdomain_record_keeps_full_names_under_the_domain() {
+        let record = SubdomainRecord::new("test", "example.com", ["*.dev.example.com", "example.com", "mail.example.com."]);
+        assert_eq!(record.subdomains, ["dev.example.com", "example.com", "mail.example.com"]);
+    }
+
+    #[test]
+    fn subdomain_record_drops_names_outside_the_domain() {
+        let record = SubdomainRecord::new("test", "example.com", ["other.com", "notexample.com", "a b", "", "ok"]);
+        assert_eq!(record.subdomains, ["ok.example.com"]);
+    }
+}
@@ -1,9 +1,15 @@
-use reqwest::{Client, Error};
+use reqwest::{Client, Response};
 use std::env;
 use std::net::IpAddr;
 use serde_json::Value;
-use std::fs::File;
 use std::io::Write;
+use serde_json::json;
+use crate::options::SearchOptions;
+use crate::target::Network;
+use crate::read_list::open_output_file;
+
+// Netlas returns 20 items per search page; anything larger goes through /download
+const NETLAS_PAGE_SIZE: usize = 20;
 
 pub fn is_domain(target: &str) -> bool {
     let domain_regex = regex::Regex::new(r"^([a-zA-Z0-9]+(-[a-zA-Z0-9]+)*\.)+[a-zA-Z]{2,}$").unwrap();
@@ -14,8 +20,24 @@ pub fn is_ip(target: &str) -> bool {
     target.parse::<IpAddr>().is_ok()
 }
 
-/* pub async fn query_netlas_domain(target: &str) -> Result<String, Error> {
-    let api_key = env::var("NETLAS_API").expect("NETLAS_API_KEY not found");
+// Auth, quota and query errors come back as a non-2xx status with {"detail": "..."}
+async fn read_netlas_response(response: Response) -> Result<Value, Box<dyn std::error::Error>> {
+    let status = response.status();
+    let response_body = response.text().await?;
+
+    if !status.is_success() {
+        let detail = serde_json::from_str::<Value>(&response_body)
+            .ok()
+            .and_then(|body| body["detail"].as_str().map(String::from))
+            .unwrap_or(response_body);
+        return Err(format!("Netlas request failed: HTTP {}: {}", status, detail).into());
+    }
+
+    Ok(serde_json::from_str(&response_body)?)
+}
+
+pub async fn query_netlas(target: &str) -> Result<Value, Box<dyn std::error::Error>> {
+    let api_key = env::var("NETLAS_API").expect("NETLAS_API not found");
 
     let client = Client::new();
 
@@ -28,67 +50,201 @@ pub fn is_ip(target: &str) -> bool {
         .send()
         .await?;
 
-    Ok(response.text().await?)
+    read_netlas_response(response).await
 }
 
-pub async fn query_netlas_ip(target: &str) -> Result<String, Error> {
-    let api_key = env::var("NETLAS_API").expect("NETLAS_API_KEY not found");
+pub async fn run_single_search_netlas(
+    target: &str,
+    output_file: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if is_ip(target) || is_domain(target) {
+        let parsed_result = query_netlas(target).await?;
+
+        match output_file {
+            Some(file_path) => {
+                let mut file = open_output_file(file_path)?;
+                writeln!(file, "Netlas: \n{}", serde_json::to_string_pretty(&parsed_result)?)?;
+            }
+            None => {
+                println!("Netlas:");
+                println!("{}", serde_json::to_string_pretty(&parsed_result)?);
+            }
+        }
+    } else {
+        println!("Invalid target: {}", target);
+    }
+    Ok(())
+}
+
+pub fn netlas_network_query(network: &Network) -> String {
+    match network {
+        Network::Asn(asn) => format!("asn.number:{}", asn),
+        Network::Org(org) => format!("whois.net.organization:\"{}\"", org.replace('"', "")),
+    }
+}
+
+// Maps the --netlas-type value onto the Netlas API collection name
+pub fn netlas_collection(datatype: &str) -> Option<&'static str> {
+    match datatype {
+        "responses" => Some("responses"),
+        "domains" => Some("domains"),
+        "whois-ip" => Some("whois_ip"),
+        "whois-domain" => Some("whois_domains"),
+        _ => None,
+    }
+}
+
+pub async fn query_netlas_count(collection: &str, query: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    let api_key = env::var("NETLAS_API").expect("NETLAS_API not found");
 
     let client = Client::new();
 
-    let url = format!("https://app.netlas.io/api/host/{}/?fields=*&source_type=include", target);
+    let url = format!("https://app.netlas.io/api/{}_count/", collection);
 
     let response = client
         .get(&url)
+        .query(&[("q", query)])
         .header("accept", "application/json")
         .header("X-API-Key", api_key)
         .send()
         .await?;
 
-    Ok(response.text().await?)
-
-
-
-}  */
+    let parsed_result = read_netlas_response(response).await?;
+    parsed_result["count"]
+        .as_u64()
+        .ok_or_else(|| "Netlas count response has no count".into())
+}
 
-pub async fn query_netlas(target: &str) -> Result<String, Error> {
+pub async fn query_netlas_search(collection: &str, query: &str, start: usize) -> Result<Value, Box<dyn std::error::Error>> {
     let api_key = env::var("NETLAS_API").expect("NETLAS_API not found");
 
     let client = Client::new();
 
-    let url = format!("https://app.netlas.io/api/host/{}/?fields=*&source_type=include", target);
+    let url = format!("https://app.netlas.io/api/{}/", collection);
 
     let response = client
         .get(&url)
+        .query(&[
+            ("q", query),
+            ("start", &start.to_string()),
+            ("fields", "*"),
+            ("source_type", "include"),
+        ])
         .header("accept", "application/json")
-        .header ("X-API-Key", api_key)
+        .header("X-API-Key", api_key)
         .send()
         .await?;
-    Ok(response.text().await?)
 
+    read_netlas_response(response).await
+}
+
+// Streams the /download body chunk by chunk so large result sets never sit in memory
+pub async fn download_netlas(
+    collection: &str,
+    query: &str,
+    size: u64,
+    output_file: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let api_key = env::var("NETLAS_API").expect("NETLAS_API not found");
+
+    let client = Client::new();
+
+    let url = format!("https://app.netlas.io/api/{}/download/", collection);
+
+    let body = json!({
+        "q": query,
+        "fields": ["*"],
+        "source_type": "include",
+        "size": size,
+        "type": "json",
+        "indices": "",
+    });
+
+    let mut response = client
+        .post(&url)
+        .header("accept", "application/json")
+        .header("X-API-Key", api_key)
+        .json(&body)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    let mut writer: Box<dyn Write> = match output_file {
+        Some(file_path) => {
+            let mut file = open_output_file(file_path)?;
+            writeln!(file, "Netlas:")?;
+            Box::new(file)
+        }
+        None => {
+            println!("Netlas:");
+            Box::new(std::io::stdout())
+        }
+    };
 
+    while let Some(chunk) = response.chunk().await? {
+        writer.write_all(&chunk)?;
+    }
+    writeln!(writer)?;
+
+    Ok(())
 }
 
-pub async fn run_single_search_netlas(
-    target: &str,
+pub async fn run_query_search_netlas(
+    query: &str,
     output_file: Option<&str>,
+    options: &SearchOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    if is_ip(target) || is_domain(target) {
-        let netlas_result = query_netlas(target).await?;
-        let parsed_result = serde_json::from_str::<Value>(&netlas_result)?;
+    let collection = match netlas_collection(&options.netlas_type) {
+        Some(collection) => collection,
+        None => {
+            println!("Invalid Netlas search type: {}", options.netlas_type);
+            return Ok(());
+        }
+    };
 
-        match output_file {
-            Some(file_path) => {
-                let mut file = File::create(file_path)?;
-                writeln!(file, "Netlas: \n{}", serde_json::to_string_pretty(&parsed_result)?)?;
-            }
-            None => {
-                println!("Netlas:");
-                println!("{}", serde_json::to_string_pretty(&parsed_result)?);
-            }
+    let count = query_netlas_count(collection, query).await?;
+    let wanted = options.limit.map_or(count, |limit| count.min(limit as u64));
+
+    if wanted as usize > NETLAS_PAGE_SIZE {
+        return download_netlas(collection, query, wanted, output_file).await;
+    }
+
+    let parsed_result = query_netlas_search(collection, query, 0).await?;
+    let items: Vec<Value> = parsed_result["items"]
+        .as_array()
+        .map(|items| items.iter().take(wanted as usize).cloned().collect())
+        .unwrap_or_default();
+
+    let result = json!({
+        "count": count,
+        "items": items,
+    });
+
+    match output_file {
+        Some(file_path) => {
+            let mut file = open_output_file(file_path)?;
+            writeln!(file, "Netlas: \n{}", serde_json::to_string_pretty(&result)?)?;
+        }
+        None => {
+            println!("Netlas:");
+            println!("{}", serde_json::to_string_pretty(&result)?);
         }
-    } else {
-        println!("Invalid target: {}", target);
     }
     Ok(())
 }
+
+pub async fn run_network_search_netlas(
+    network: &Network,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let query = netlas_network_query(network);
+    // An ASN can have millions of responses, so like the other network searches only the
+    // first page is fetched unless --limit asks for more
+    let network_options = SearchOptions {
+        netlas_type: "responses".to_string(),
+        limit: Some(options.limit.unwrap_or(NETLAS_PAGE_SIZE)),
+        ..options.clone()
+    };
+    run_query_search_netlas(&query, output_file, &network_options).await
+}
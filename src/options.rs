@@ -0,0 +1,88 @@
+use clap::ArgMatches;
+use std::error::Error;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use crate::cve_feed::CveFeed;
+use crate::dns_search::parse_resolver;
+
+// Settings that apply across providers, collected once from the command line
+#[derive(Debug, Default, Clone)]
+pub struct SearchOptions {
+    pub summary: bool,
+    pub limit: Option<usize>,
+    pub netlas_type: String,
+    pub lang: String,
+    pub min_cvss: Option<f64>,
+    pub cve_feed: Option<Arc<CveFeed>>,
+    pub concurrency: usize,
+    pub no_chaos: bool,
+    pub fullhunt_host: bool,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub pivot: bool,
+    pub vt_relations: Vec<String>,
+    pub prefix_file: Option<String>,
+    pub max_age_days: u32,
+    pub urlscan_submit: bool,
+    // Set for targets derived from the user's target, see for_pivot
+    pub is_pivot: bool,
+    pub urlscan_visibility: String,
+    pub rdap_bootstrap: Option<String>,
+    pub resolver: Option<SocketAddr>,
+}
+
+impl SearchOptions {
+    pub fn from_matches(matches: &ArgMatches) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            summary: matches.is_present("summary"),
+            limit: matches.value_of("limit").and_then(|limit| limit.parse().ok()),
+            netlas_type: matches.value_of("netlas_type").unwrap_or("responses").to_string(),
+            lang: matches.value_of("lang").unwrap_or("en").to_string(),
+            min_cvss: matches.value_of("min_cvss").and_then(|score| score.parse().ok()),
+            cve_feed: load_cve_feed(matches)?.map(Arc::new),
+            concurrency: matches.value_of("concurrency").and_then(|n| n.parse().ok()).unwrap_or(50),
+            no_chaos: matches.is_present("no_chaos"),
+            fullhunt_host: matches.is_present("fullhunt_host"),
+            first_name: matches.value_of("first_name").map(String::from),
+            last_name: matches.value_of("last_name").map(String::from),
+            pivot: matches.is_present("pivot"),
+            vt_relations: matches.values_of("vt_relations").unwrap_or_default().map(String::from).collect(),
+            prefix_file: matches.value_of("prefix_file").map(String::from),
+            max_age_days: matches.value_of("max_age_days").and_then(|days| days.parse().ok()).unwrap_or(90),
+            urlscan_submit: matches.is_present("urlscan_submit"),
+            is_pivot: false,
+            urlscan_visibility: matches.value_of("urlscan_visibility").unwrap_or("private").to_string(),
+            rdap_bootstrap: matches.value_of("rdap_bootstrap").map(String::from),
+            resolver: matches.value_of("resolver").and_then(parse_resolver),
+        })
+    }
+
+    // Settings for targets derived from the user's target (pivots), which must never
+    // be submitted to third parties such as urlscan.io. Search types that don't apply to
+    // the derived target are skipped quietly instead of reported as invalid.
+    pub fn for_pivot(&self) -> Self {
+        Self {
+            urlscan_submit: false,
+            is_pivot: true,
+            ..self.clone()
+        }
+    }
+}
+
+fn load_cve_feed(matches: &ArgMatches) -> Result<Option<CveFeed>, Box<dyn Error>> {
+    if !matches.is_present("nvd_feed") && !matches.is_present("kev_feed") {
+        return Ok(None);
+    }
+
+    let mut feed = CveFeed::default();
+    for path in matches.values_of("nvd_feed").unwrap_or_default() {
+        feed.load_nvd(path)
+            .map_err(|err| format!("failed to load NVD feed {}: {}", path, err))?;
+    }
+    if let Some(path) = matches.value_of("kev_feed") {
+        feed.load_kev(path)
+            .map_err(|err| format!("failed to load KEV catalog {}: {}", path, err))?;
+    }
+
+    Ok(Some(feed))
+}
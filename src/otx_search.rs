@@ -0,0 +1,256 @@
+use reqwest::Client;
+use std::env;
+use std::error::Error;
+use std::io::Write;
+use std::net::IpAddr;
+use serde_json::Value;
+use crate::model::{value_to_string, IndicatorReport, RelatedIndicator, ThreatIntel};
+use crate::options::SearchOptions;
+use crate::error::SearchError;
+use crate::target::{is_domain, is_hash};
+use crate::read_list::open_output_file;
+
+// Entries kept from the passive_dns, url_list and malware sections when no --limit is given
+const OTX_DEFAULT_LIMIT: usize = 10;
+
+// Indicator lookups work without a key at a lower rate limit, so OTX_API is optional
+pub fn get_otx_api_key() -> Option<String> {
+    env::var("OTX_API").ok()
+}
+
+// The OTX indicator type for a target and the sections worth fetching for it
+pub fn otx_indicator_type(target: &str) -> Option<(&'static str, &'static [&'static str])> {
+    const HOST_SECTIONS: &[&str] = &["general", "reputation", "passive_dns", "url_list", "malware"];
+    const NAME_SECTIONS: &[&str] = &["general", "passive_dns", "url_list", "malware"];
+
+    match target.parse::<IpAddr>() {
+        Ok(IpAddr::V4(_)) => Some(("IPv4", HOST_SECTIONS)),
+        Ok(IpAddr::V6(_)) => Some(("IPv6", HOST_SECTIONS)),
+        Err(_) if is_hash(target) => Some(("file", &["general"])),
+        // OTX keeps registered domains and the hostnames under them apart; names are tried
+        // as domains first, and search_otx falls back to hostname when OTX rejects that
+        Err(_) if is_domain(target) => Some(("domain", NAME_SECTIONS)),
+        Err(_) => None,
+    }
+}
+
+pub async fn query_otx(
+    indicator_type: &str,
+    indicator: &str,
+    section: &str,
+    limit: usize,
+) -> Result<Value, Box<dyn Error>> {
+    let url = format!(
+        "https://otx.alienvault.com/api/v1/indicators/{}/{}/{}",
+        indicator_type, indicator, section
+    );
+
+    let client = Client::new();
+
+    let mut request = client.get(&url);
+    if matches!(section, "url_list" | "malware") {
+        request = request.query(&[("limit", limit.to_string()), ("page", "1".to_string())]);
+    }
+    if let Some(api_key) = get_otx_api_key() {
+        request = request.header("X-OTX-API-KEY", api_key);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|err| SearchError::new(err.to_string()))?;
+
+    if !response.status().is_success() {
+        return Err(Box::new(SearchError::new(format!(
+            "OTX {} section returned HTTP {}",
+            section,
+            response.status()
+        ))));
+    }
+
+    let response_body = response
+        .text()
+        .await
+        .map_err(|err| SearchError::new(err.to_string()))?;
+
+    Ok(serde_json::from_str(&response_body)?)
+}
+
+// Pulse names, their tags, malware families and adversaries, deduplicated
+pub fn parse_otx_general(general: &Value, report: &mut IndicatorReport) {
+    let mut intel = ThreatIntel {
+        pulse_count: general["pulse_info"]["count"].as_u64().unwrap_or(0),
+        ..Default::default()
+    };
+
+    for pulse in general["pulse_info"]["pulses"].as_array().into_iter().flatten() {
+        push_unique(&mut intel.pulses, value_to_string(&pulse["name"]));
+        push_unique(&mut intel.adversaries, value_to_string(&pulse["adversary"]));
+        for tag in pulse["tags"].as_array().into_iter().flatten() {
+            push_unique(&mut intel.tags, value_to_string(tag));
+        }
+        for family in pulse["malware_families"].as_array().into_iter().flatten() {
+            // Older pulses list plain names, newer ones {"display_name": ...}
+            let name = value_to_string(&family["display_name"]).or_else(|| value_to_string(family));
+            push_unique(&mut intel.malware_families, name);
+        }
+    }
+
+    report.reputation = general["reputation"].as_i64();
+    report.threat_intel = Some(intel);
+
+    for key in ["asn", "country_name", "whois", "type_title"] {
+        if let Some(value) = value_to_string(&general[key]) {
+            report.details.insert(key.to_string(), Value::String(value));
+        }
+    }
+}
+
+fn push_unique(list: &mut Vec<String>, value: Option<String>) {
+    if let Some(value) = value {
+        if !list.contains(&value) {
+            list.push(value);
+        }
+    }
+}
+
+// List sections keep at most `limit` entries; passive_dns has no server-side limit
+pub fn parse_otx_section(section: &str, result: &Value, limit: usize, report: &mut IndicatorReport) {
+    match section {
+        "general" => parse_otx_general(result, report),
+        "reputation" => {
+            if let Some(score) = result["reputation"]["threat_score"].as_i64() {
+                report.details.insert("threat_score".to_string(), Value::from(score));
+            }
+        }
+        "passive_dns" => {
+            let target_is_ip = report.kind == "ip";
+            for record in result["passive_dns"].as_array().into_iter().flatten().take(limit) {
+                // IPs list the hostnames seen on them, names list the addresses they resolved to
+                let (kind, value) = if target_is_ip {
+                    ("domain", record["hostname"].as_str())
+                } else {
+                    ("ip", record["address"].as_str())
+                };
+                push_related(report, "passive_dns", kind, value, record["last"].as_str());
+            }
+        }
+        "url_list" => {
+            for entry in result["url_list"].as_array().into_iter().flatten().take(limit) {
+                push_related(report, "url_list", "url", entry["url"].as_str(), entry["date"].as_str());
+            }
+        }
+        "malware" => {
+            for sample in result["data"].as_array().into_iter().flatten().take(limit) {
+                push_related(report, "malware", "file", sample["hash"].as_str(), sample["date"].as_str());
+            }
+        }
+        _ => {}
+    }
+}
+
+fn push_related(report: &mut IndicatorReport, relation: &str, kind: &str, value: Option<&str>, date: Option<&str>) {
+    let value = match value {
+        Some(value) if !value.is_empty() => value,
+        _ => return,
+    };
+    if report.related.iter().any(|r| r.relation == relation && r.value == value) {
+        return;
+    }
+
+    report.related.push(RelatedIndicator {
+        relation: relation.to_string(),
+        kind: kind.to_string(),
+        value: value.to_string(),
+        date: date.map(String::from),
+    });
+}
+
+pub async fn search_otx(target: &str, options: &SearchOptions) -> Result<IndicatorReport, Box<dyn Error>> {
+    let (mut indicator_type, sections) = otx_indicator_type(target)
+        .ok_or_else(|| SearchError::new(format!("Invalid target: {}", target)))?;
+
+    let kind = match indicator_type {
+        "IPv4" | "IPv6" => "ip",
+        "file" => "file",
+        _ => "domain",
+    };
+    let mut report = IndicatorReport::new("OTX", target, kind);
+    let limit = options.limit.unwrap_or(OTX_DEFAULT_LIMIT).max(1);
+
+    // Every section list starts with general, which also tells domains from hostnames
+    let general = match query_otx(indicator_type, target, "general", limit).await {
+        Ok(general) => general,
+        Err(_) if indicator_type == "domain" => {
+            indicator_type = "hostname";
+            query_otx(indicator_type, target, "general", limit).await?
+        }
+        Err(err) => return Err(err),
+    };
+    parse_otx_section("general", &general, limit, &mut report);
+
+    for section in sections.iter().filter(|section| **section != "general") {
+        let result = query_otx(indicator_type, target, section, limit).await?;
+        parse_otx_section(section, &result, limit, &mut report);
+    }
+
+    Ok(report)
+}
+
+pub async fn run_single_search_otx(
+    target: &str,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn Error>> {
+    let report = search_otx(target, options).await?;
+
+    let otx_result = if options.summary {
+        report.summary_line()
+    } else {
+        serde_json::to_string_pretty(&report)?
+    };
+
+    match output_file {
+        Some(file_path) => {
+            let mut file = open_output_file(file_path)?;
+            writeln!(file, "OTX:\n{}", otx_result)?;
+        }
+        None => {
+            println!("OTX:");
+            println!("{}", otx_result);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn names_are_tried_as_domains_first() {
+        for name in ["example.com", "example.co.uk", "example.com.au", "www.example.com"] {
+            assert_eq!(otx_indicator_type(name).map(|(kind, _)| kind), Some("domain"));
+        }
+        assert_eq!(otx_indicator_type("192.0.2.1").map(|(kind, _)| kind), Some("IPv4"));
+        assert_eq!(otx_indicator_type("2001:db8::1").map(|(kind, _)| kind), Some("IPv6"));
+        assert_eq!(otx_indicator_type(&"b".repeat(40)).map(|(kind, _)| kind), Some("file"));
+        assert!(otx_indicator_type("not a target").is_none());
+    }
+
+    #[test]
+    fn passive_dns_respects_the_limit() {
+        let result = json!({"passive_dns": [
+            {"address": "192.0.2.1", "last": "2024-01-03"},
+            {"address": "192.0.2.2", "last": "2024-01-02"},
+            {"address": "192.0.2.3", "last": "2024-01-01"}
+        ]});
+        let mut report = IndicatorReport::new("OTX", "example.com", "domain");
+        parse_otx_section("passive_dns", &result, 2, &mut report);
+
+        let values: Vec<&str> = report.related.iter().map(|r| r.value.as_str()).collect();
+        assert_eq!(values, ["192.0.2.1", "192.0.2.2"]);
+        assert!(report.related.iter().all(|r| r.kind == "ip"));
+    }
+}
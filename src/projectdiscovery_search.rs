@@ -1,11 +1,13 @@
 use reqwest::Client;
 use std::env;
 use regex::Regex;
-use std::fs::File;
 use std::io::Write;
 use serde_json::Value;
 use std::process::Command;
 use which::which;
+use crate::model::SubdomainRecord;
+use crate::options::SearchOptions;
+use crate::read_list::open_output_file;
 
 // Function to check if the target is a valid domain
 pub fn is_domain(target: &str) -> bool {
@@ -32,41 +34,76 @@ pub async fn query_projectdiscovery(domain: &str) -> Result<String, Box<dyn std:
     Ok(response_body)
 }
 
+// The API returns bare labels: {"domain": "example.com", "subdomains": ["www", ...]}
+pub fn parse_projectdiscovery(domain: &str, response: &Value) -> SubdomainRecord {
+    let labels = response["subdomains"]
+        .as_array()
+        .map(|labels| labels.iter().filter_map(|label| label.as_str()).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    SubdomainRecord::new("ProjectDiscovery", domain, labels)
+}
+
+// Runs the chaos client if it is on PATH, returning None when it isn't installed
+pub fn query_chaos_binary(domain: &str) -> Result<Option<SubdomainRecord>, Box<dyn std::error::Error>> {
+    let chaos_path = match which("chaos") {
+        Ok(path) => path,
+        Err(_) => return Ok(None),
+    };
+
+    let api_key = env::var("PROJECTDISCOVERY_API").expect("PROJECTDISCOVERY_API not found");
+    let chaos_output = Command::new(chaos_path)
+        .arg("-d")
+        .arg(domain)
+        .arg("-silent")
+        .arg("-key")
+        .arg(api_key)
+        .output()?;
+
+    if !chaos_output.status.success() {
+        return Err(format!(
+            "chaos exited with {}: {}",
+            chaos_output.status,
+            String::from_utf8_lossy(&chaos_output.stderr).trim()
+        )
+        .into());
+    }
+
+    // One subdomain per line
+    let chaos_stdout = String::from_utf8_lossy(&chaos_output.stdout);
+    Ok(Some(SubdomainRecord::new("ProjectDiscovery", domain, chaos_stdout.lines())))
+}
+
 // Function to perform a single search using ProjectDiscovery
 pub async fn run_single_search_projectdiscovery(
     target: &str,
     output_file: Option<&str>,
+    options: &SearchOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
     if is_domain(target) {
-        let check_chaos = Command::new("chaos")
-            .arg("--help")
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
-            .status();
-
-        if let Ok(_) = check_chaos {
-            let api_key = env::var("PROJECTDISCOVERY_API").expect("PROJECTDISCOVERY_API not found");
-            let chaos_output = Command::new("chaos")
-                .arg("-d")
-                .arg(target)
-                .arg("-key")
-                .arg(api_key)
-                .output()?;
-
-            let chaos_stdout = String::from_utf8_lossy(&chaos_output.stdout);
-            println!("Chaos:\n{}", chaos_stdout);  // Print to terminal
-
-            if let Some(output_file) = output_file {
-                let mut file = File::create(output_file)?;
-                writeln!(file, "Chaos:\n{}", chaos_stdout)?;
-            }
+        let chaos_record = if options.no_chaos {
+            None
         } else {
-            let projectdiscovery_result = query_projectdiscovery(target).await?;
-            let parsed_result = serde_json::from_str::<Value>(&projectdiscovery_result)?;
+            query_chaos_binary(target)?
+        };
+
+        let record = match chaos_record {
+            Some(record) => record,
+            None => {
+                let projectdiscovery_result = query_projectdiscovery(target).await?;
+                let parsed_result = serde_json::from_str::<Value>(&projectdiscovery_result)?;
+                parse_projectdiscovery(target, &parsed_result)
+            }
+        };
 
-            if let Some(output_file) = output_file {
-                let mut file = File::create(output_file)?;
-                writeln!(file, "ProjectDiscovery:\n{}", serde_json::to_string_pretty(&parsed_result)?)?;
+        match output_file {
+            Some(file_path) => {
+                let mut file = open_output_file(file_path)?;
+                writeln!(file, "ProjectDiscovery:\n{}", serde_json::to_string_pretty(&record)?)?;
+            }
+            None => {
+                println!("ProjectDiscovery:");
+                println!("{}", serde_json::to_string_pretty(&record)?);
             }
         }
     } else {
@@ -74,6 +111,3 @@ pub async fn run_single_search_projectdiscovery(
     }
     Ok(())
 }
-
-
-
@@ -0,0 +1,349 @@
+use reqwest::{Client, StatusCode};
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::Write;
+use std::net::IpAddr;
+use std::path::Path;
+use serde_json::Value;
+use crate::model::RegistrationRecord;
+use crate::options::SearchOptions;
+use crate::error::SearchError;
+use crate::target::is_domain;
+use crate::read_list::open_output_file;
+
+const IANA_BOOTSTRAP_URL: &str = "https://data.iana.org/rdap";
+
+pub fn is_ip(target: &str) -> bool {
+    target.parse::<IpAddr>().is_ok()
+}
+
+// One of IANA's bootstrap registries (dns, ipv4, ipv6). With --rdap-bootstrap the file is
+// read from that directory, and downloaded into it the first time, so later runs work offline.
+pub async fn load_rdap_bootstrap(registry: &str, cache_dir: Option<&str>) -> Result<Value, Box<dyn Error>> {
+    let file_name = format!("{}.json", registry);
+
+    if let Some(dir) = cache_dir {
+        let path = Path::new(dir).join(&file_name);
+        if path.exists() {
+            return Ok(serde_json::from_str(&fs::read_to_string(&path)?)?);
+        }
+    }
+
+    let url = format!("{}/{}", IANA_BOOTSTRAP_URL, file_name);
+    let response_body = Client::new()
+        .get(&url)
+        .send()
+        .await
+        .map_err(|err| SearchError::new(err.to_string()))?
+        .text()
+        .await
+        .map_err(|err| SearchError::new(err.to_string()))?;
+    let bootstrap: Value = serde_json::from_str(&response_body)?;
+
+    if let Some(dir) = cache_dir {
+        fs::create_dir_all(dir)?;
+        fs::write(Path::new(dir).join(&file_name), &response_body)?;
+    }
+
+    Ok(bootstrap)
+}
+
+// Prefix length when the prefix (e.g. "2001:200::/23") covers the address
+fn prefix_match(prefix: &str, ip: &IpAddr) -> Option<u32> {
+    let (network, length) = prefix.split_once('/')?;
+    let length: u32 = length.parse().ok()?;
+
+    let covers = match (network.parse::<IpAddr>().ok()?, ip) {
+        (IpAddr::V4(network), IpAddr::V4(ip)) if length <= 32 => {
+            let mask = u32::MAX.checked_shl(32 - length).unwrap_or(0);
+            u32::from(network) & mask == u32::from(*ip) & mask
+        }
+        (IpAddr::V6(network), IpAddr::V6(ip)) if length <= 128 => {
+            let mask = u128::MAX.checked_shl(128 - length).unwrap_or(0);
+            u128::from(network) & mask == u128::from(*ip) & mask
+        }
+        _ => false,
+    };
+
+    covers.then_some(length)
+}
+
+// Bootstrap services are [[entries], [base urls]]; the most specific entry wins,
+// and an https base URL is preferred over plain http. Returns the matched entry
+// (a prefix or a TLD such as "co.uk") along with the base URL.
+pub fn rdap_service(bootstrap: &Value, target: &str) -> Option<(String, String)> {
+    let ip = target.parse::<IpAddr>().ok();
+    let domain = target.trim_end_matches('.').to_lowercase();
+    let mut best: Option<(usize, &str, &Vec<Value>)> = None;
+
+    for service in bootstrap["services"].as_array().into_iter().flatten() {
+        let urls = match service[1].as_array() {
+            Some(urls) => urls,
+            None => continue,
+        };
+        for entry in service[0].as_array().into_iter().flatten().filter_map(Value::as_str) {
+            let specificity = match &ip {
+                Some(ip) => prefix_match(entry, ip).map(|length| length as usize),
+                None => {
+                    let entry = entry.to_lowercase();
+                    (domain == entry || domain.ends_with(&format!(".{}", entry))).then_some(entry.len())
+                }
+            };
+            if let Some(specificity) = specificity {
+                if best.is_none_or(|(current, _, _)| specificity > current) {
+                    best = Some((specificity, entry, urls));
+                }
+            }
+        }
+    }
+
+    let (_, entry, urls) = best?;
+    let urls: Vec<&str> = urls.iter().filter_map(Value::as_str).collect();
+    urls.iter()
+        .find(|url| url.starts_with("https://"))
+        .or(urls.first())
+        .map(|url| (entry.to_lowercase(), url.to_string()))
+}
+
+// Registries only know registered domains, so www.example.co.uk is looked up as
+// itself, then example.co.uk, stopping before the bootstrap entry (co.uk) itself
+pub fn rdap_domain_names(domain: &str, suffix: &str) -> Vec<String> {
+    let domain = domain.trim_end_matches('.').to_lowercase();
+    let suffix_labels = suffix.split('.').count();
+    let labels: Vec<&str> = domain.split('.').collect();
+
+    (0..labels.len().saturating_sub(suffix_labels))
+        .map(|start| labels[start..].join("."))
+        .collect()
+}
+
+// None when the registry has no such object (HTTP 404)
+pub async fn query_rdap(base_url: &str, path: &str) -> Result<Option<Value>, Box<dyn Error>> {
+    let url = format!("{}/{}", base_url.trim_end_matches('/'), path);
+
+    // RIRs answer for each other's space with a redirect, which reqwest follows
+    let response = Client::new()
+        .get(&url)
+        .header("Accept", "application/rdap+json")
+        .send()
+        .await
+        .map_err(|err| SearchError::new(err.to_string()))?;
+
+    let status = response.status();
+    if status == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let response_body = response
+        .text()
+        .await
+        .map_err(|err| SearchError::new(err.to_string()))?;
+    if !status.is_success() {
+        return Err(Box::new(SearchError::new(format!("RDAP lookup {} failed: HTTP {}", url, status))));
+    }
+
+    Ok(Some(serde_json::from_str(&response_body)?))
+}
+
+// A vCard property such as "fn" or "email", from ["vcard", [[name, params, type, value], ...]]
+fn vcard_value(entity: &Value, property: &str) -> Option<String> {
+    entity["vcardArray"][1]
+        .as_array()?
+        .iter()
+        .find(|field| field[0].as_str() == Some(property))
+        .and_then(|field| field[3].as_str())
+        .filter(|value| !value.is_empty())
+        .map(|value| value.trim_start_matches("tel:").to_string())
+}
+
+// Entities nest (the abuse contact usually hangs off the registrar or registrant),
+// so the whole tree is searched for the first entity with the role
+fn find_entity<'a>(entities: &'a Value, role: &str) -> Option<&'a Value> {
+    for entity in entities.as_array().into_iter().flatten() {
+        let has_role = entity["roles"]
+            .as_array()
+            .is_some_and(|roles| roles.iter().any(|r| r.as_str() == Some(role)));
+        if has_role {
+            return Some(entity);
+        }
+        if let Some(nested) = find_entity(&entity["entities"], role) {
+            return Some(nested);
+        }
+    }
+    None
+}
+
+fn event_date(response: &Value, action: &str) -> Option<String> {
+    response["events"]
+        .as_array()?
+        .iter()
+        .find(|event| event["eventAction"].as_str() == Some(action))
+        .and_then(|event| event["eventDate"].as_str())
+        .map(String::from)
+}
+
+fn string_list(value: &Value, key: &str) -> Vec<String> {
+    value
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| if key.is_empty() { item.as_str() } else { item[key].as_str() })
+                .map(|item| item.to_lowercase())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn parse_rdap(target: &str, kind: &str, server: &str, response: &Value) -> RegistrationRecord {
+    let entities = &response["entities"];
+    let registrant = find_entity(entities, "registrant");
+    let abuse = find_entity(entities, "abuse");
+
+    let mut record = RegistrationRecord {
+        source: "RDAP".to_string(),
+        target: target.to_string(),
+        kind: kind.to_string(),
+        server: server.to_string(),
+        handle: response["handle"].as_str().map(String::from),
+        name: response["name"].as_str().or(response["ldhName"].as_str()).map(String::from),
+        registrant_org: registrant.and_then(|entity| vcard_value(entity, "org").or_else(|| vcard_value(entity, "fn"))),
+        registrar: find_entity(entities, "registrar").and_then(|entity| vcard_value(entity, "fn")),
+        abuse_email: abuse.and_then(|entity| vcard_value(entity, "email")),
+        abuse_phone: abuse.and_then(|entity| vcard_value(entity, "tel")),
+        country: response["country"].as_str().map(String::from),
+        registered: event_date(response, "registration"),
+        last_changed: event_date(response, "last changed"),
+        expires: event_date(response, "expiration"),
+        status: string_list(&response["status"], ""),
+        nameservers: string_list(&response["nameservers"], "ldhName"),
+        ..Default::default()
+    };
+
+    if let (Some(start), Some(end)) = (response["startAddress"].as_str(), response["endAddress"].as_str()) {
+        record.range = Some(format!("{} - {}", start, end));
+    }
+    for cidr in response["cidr0_cidrs"].as_array().into_iter().flatten() {
+        let prefix = cidr["v4prefix"].as_str().or(cidr["v6prefix"].as_str());
+        if let (Some(prefix), Some(length)) = (prefix, cidr["length"].as_u64()) {
+            record.cidrs.push(format!("{}/{}", prefix, length));
+        }
+    }
+
+    record
+}
+
+// None when no registry has data for the target, which is a normal answer for
+// unregistered names and unallocated address space
+pub async fn search_rdap(target: &str, options: &SearchOptions) -> Result<Option<RegistrationRecord>, Box<dyn Error>> {
+    let registry = match target.parse::<IpAddr>() {
+        Ok(IpAddr::V4(_)) => "ipv4",
+        Ok(IpAddr::V6(_)) => "ipv6",
+        Err(_) => "dns",
+    };
+
+    let bootstrap = load_rdap_bootstrap(registry, options.rdap_bootstrap.as_deref()).await?;
+    let (entry, server) = match rdap_service(&bootstrap, target) {
+        Some(service) => service,
+        None => return Ok(None),
+    };
+
+    if registry != "dns" {
+        let response = query_rdap(&server, &format!("ip/{}", target)).await?;
+        return Ok(response.map(|response| parse_rdap(target, "ip", &server, &response)));
+    }
+
+    for name in rdap_domain_names(target, &entry) {
+        if let Some(response) = query_rdap(&server, &format!("domain/{}", name)).await? {
+            return Ok(Some(parse_rdap(target, "domain", &server, &response)));
+        }
+    }
+    Ok(None)
+}
+
+pub async fn run_single_search_rdap(
+    target: &str,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn Error>> {
+    if !is_ip(target) && !is_domain(target) {
+        println!("Invalid target: {}", target);
+        return Ok(());
+    }
+
+    let rdap_result = match search_rdap(target, options).await? {
+        Some(record) if options.summary => record.summary_line(),
+        Some(record) => serde_json::to_string_pretty(&record)?,
+        None => format!("No registration data found for {}", target),
+    };
+
+    match output_file {
+        Some(file_path) => {
+            let mut file = open_output_file(file_path)?;
+            writeln!(file, "RDAP:\n{}", rdap_result)?;
+        }
+        None => {
+            println!("RDAP:");
+            println!("{}", rdap_result);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn prefixes_match_by_address_family() {
+        let ip: IpAddr = "198.51.100.7".parse().unwrap();
+        assert_eq!(prefix_match("198.0.0.0/8", &ip), Some(8));
+        assert_eq!(prefix_match("198.51.100.0/24", &ip), Some(24));
+        assert_eq!(prefix_match("0.0.0.0/0", &ip), Some(0));
+        assert_eq!(prefix_match("199.0.0.0/8", &ip), None);
+        assert_eq!(prefix_match("2001:db8::/32", &ip), None);
+        assert_eq!(prefix_match("198.0.0.0/33", &ip), None);
+
+        let ip6: IpAddr = "2001:db8::1".parse().unwrap();
+        assert_eq!(prefix_match("2001:db8::/32", &ip6), Some(32));
+        assert_eq!(prefix_match("2001:db9::/32", &ip6), None);
+    }
+
+    #[test]
+    fn most_specific_bootstrap_entry_wins() {
+        let ipv4 = json!({"services": [
+            [["198.0.0.0/8"], ["http://rdap.arin.example/", "https://rdap.arin.example/"]],
+            [["198.51.100.0/24"], ["http://rdap.ripe.example/"]],
+            [["203.0.113.0/24"]]
+        ]});
+        assert_eq!(rdap_service(&ipv4, "198.51.100.7").map(|(_, url)| url).as_deref(), Some("http://rdap.ripe.example/"));
+        assert_eq!(rdap_service(&ipv4, "198.1.2.3").map(|(_, url)| url).as_deref(), Some("https://rdap.arin.example/"));
+        assert_eq!(rdap_service(&ipv4, "203.0.113.1"), None);
+
+        let dns = json!({"services": [
+            [["uk"], ["https://rdap.nominet.example/uk/"]],
+            [["co.uk", "org.uk"], ["https://rdap.nominet.example/co.uk/"]],
+            [["com"], ["https://rdap.verisign.example/com/v1/"]]
+        ]});
+        assert_eq!(
+            rdap_service(&dns, "Example.co.uk."),
+            Some(("co.uk".to_string(), "https://rdap.nominet.example/co.uk/".to_string()))
+        );
+        assert_eq!(
+            rdap_service(&dns, "example.uk"),
+            Some(("uk".to_string(), "https://rdap.nominet.example/uk/".to_string()))
+        );
+        assert_eq!(rdap_service(&dns, "example.scom"), None);
+    }
+
+    #[test]
+    fn subdomains_fall_back_to_the_registered_domain() {
+        assert_eq!(rdap_domain_names("www.Example.com", "com"), ["www.example.com", "example.com"]);
+        assert_eq!(
+            rdap_domain_names("a.b.example.co.uk.", "co.uk"),
+            ["a.b.example.co.uk", "b.example.co.uk", "example.co.uk"]
+        );
+        assert_eq!(rdap_domain_names("example.com", "com"), ["example.com"]);
+        assert!(rdap_domain_names("com", "com").is_empty());
+    }
+}
@@ -1,5 +1,6 @@
-use std::fs::File;
+use std::fs::{File, OpenOptions};
 use std::io::{self, BufRead};
+use std::net::{Ipv4Addr, Ipv6Addr};
 use std::path::Path;
 
 pub fn read_targets_from_file<P>(filename: P) -> io::Result<Vec<String>>
@@ -9,3 +10,106 @@ where P: AsRef<Path>, {
     let lines: io::Result<Vec<String>> = reader.lines().collect();
     lines
 }
+
+// Every provider appends to the -o file, so results from several search types and targets
+// end up in one file. main truncates it once before the searches start.
+pub fn open_output_file<P>(filename: P) -> io::Result<File>
+where P: AsRef<Path>, {
+    OpenOptions::new().create(true).append(true).open(filename)
+}
+
+// Expands an IPv4 CIDR such as 192.0.2.0/24 into its host addresses; any other target is
+// returned unchanged. Ranges are capped at /16 so a typo can't queue billions of lookups.
+pub fn expand_target(target: &str) -> Result<Vec<String>, String> {
+    let (network, prefix) = match target.split_once('/') {
+        Some(parts) => parts,
+        None => return Ok(vec![target.to_string()]),
+    };
+
+    if network.parse::<Ipv6Addr>().is_ok() {
+        return Err(format!("IPv6 CIDR ranges are not supported: {}", target));
+    }
+    let network: Ipv4Addr = network
+        .parse()
+        .map_err(|_| format!("Invalid CIDR target: {}", target))?;
+    let prefix: u32 = prefix
+        .parse()
+        .ok()
+        .filter(|prefix| *prefix <= 32)
+        .ok_or_else(|| format!("Invalid CIDR target: {}", target))?;
+    if prefix < 16 {
+        return Err(format!("CIDR ranges larger than /16 are not supported: {}", target));
+    }
+
+    let mask = u32::MAX << (32 - prefix);
+    let first = u32::from(network) & mask;
+    let last = first | !mask;
+
+    // Skip the network and broadcast addresses unless the range is a /31 or /32
+    let (first, last) = if prefix <= 30 { (first + 1, last - 1) } else { (first, last) };
+
+    Ok((first..=last).map(|addr| Ipv4Addr::from(addr).to_string()).collect())
+}
+
+// Prefixes originated by an ASN, read from a local prefix-to-AS dump. Both CAIDA pfx2as
+// lines ("1.0.0.0<TAB>24<TAB>13335") and "1.0.0.0/24 13335" style tables are understood;
+// multi-origin entries such as "13335_4444" or "13335,4444" match any of their ASNs.
+pub fn read_asn_prefixes<P>(filename: P, asn: u32) -> io::Result<Vec<String>>
+where P: AsRef<Path>, {
+    let file = File::open(&filename)?;
+    let reader = io::BufReader::new(file);
+    let mut prefixes = Vec::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (prefix, origins) = match fields.as_slice() {
+            [prefix, origins, ..] if prefix.contains('/') => (prefix.to_string(), *origins),
+            [network, length, origins, ..] => (format!("{}/{}", network, length), *origins),
+            _ => continue,
+        };
+
+        let matches_asn = origins
+            .split(['_', ','])
+            .any(|origin| origin.trim_start_matches("AS").parse::<u32>() == Ok(asn));
+        if matches_asn && !prefixes.contains(&prefix) {
+            prefixes.push(prefix);
+        }
+    }
+
+    Ok(prefixes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn cidrs_expand_to_host_addresses() {
+        assert_eq!(expand_target("192.0.2.1").unwrap(), ["192.0.2.1"]);
+        assert_eq!(expand_target("192.0.2.0/30").unwrap(), ["192.0.2.1", "192.0.2.2"]);
+        assert_eq!(expand_target("192.0.2.8/31").unwrap(), ["192.0.2.8", "192.0.2.9"]);
+        assert_eq!(expand_target("198.51.0.0/16").unwrap().len(), 65534);
+        assert!(expand_target("10.0.0.0/8").unwrap_err().contains("larger than /16"));
+        assert!(expand_target("2001:db8::/48").unwrap_err().contains("IPv6"));
+        assert!(expand_target("192.0.2.0/33").is_err());
+    }
+
+    #[test]
+    fn asn_prefixes_are_read_from_either_dump_format() {
+        let path = std::env::temp_dir().join(format!("rust_recon_pfx2as_{}.txt", std::process::id()));
+        let mut file = File::create(&path).unwrap();
+        writeln!(file, "1.0.0.0\t24\t13335").unwrap();
+        writeln!(file, "1.1.1.0\t24\t13335_4444").unwrap();
+        writeln!(file, "192.0.2.0/24 AS64500,13335").unwrap();
+        writeln!(file, "1.0.0.0/24 13335").unwrap();
+        writeln!(file, "198.51.100.0\t24\t133350").unwrap();
+        writeln!(file, "garbage").unwrap();
+        drop(file);
+
+        let prefixes = read_asn_prefixes(&path, 13335).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(prefixes, ["1.0.0.0/24", "1.1.1.0/24", "192.0.2.0/24"]);
+    }
+}
@@ -0,0 +1,199 @@
+use reqwest::Client;
+use std::env;
+use std::error::Error;
+use std::io::Write;
+use std::net::IpAddr;
+use serde_json::{json, Value};
+use crate::model::SubdomainRecord;
+use crate::options::SearchOptions;
+use crate::error::SearchError;
+use crate::target::is_domain;
+use crate::read_list::open_output_file;
+
+pub fn is_ip(target: &str) -> bool {
+    target.parse::<IpAddr>().is_ok()
+}
+
+pub fn get_securitytrails_api_key() -> Option<String> {
+    env::var("SECURITYTRAILS_API").ok()
+}
+
+pub async fn query_securitytrails(path: &str, params: &[(&str, String)]) -> Result<Value, Box<dyn Error>> {
+    let api_key = get_securitytrails_api_key().expect("SECURITYTRAILS_API not found");
+
+    let url = format!("https://api.securitytrails.com/v1/{}", path);
+
+    let client = Client::new();
+
+    let response = client
+        .get(&url)
+        .query(params)
+        .header("APIKEY", api_key)
+        .header("accept", "application/json")
+        .send()
+        .await
+        .map_err(|err| SearchError::new(err.to_string()))?;
+
+    let status = response.status();
+    let response_body = response
+        .text()
+        .await
+        .map_err(|err| SearchError::new(err.to_string()))?;
+
+    // Quota, auth and plan errors come back as {"message": "..."}, usually with a 4xx status
+    let parsed_result: Value = match serde_json::from_str(&response_body) {
+        Ok(parsed_result) => parsed_result,
+        Err(_) if !status.is_success() => {
+            return Err(Box::new(SearchError::new(format!("SecurityTrails request failed: HTTP {}", status))));
+        }
+        Err(err) => return Err(Box::new(err)),
+    };
+    if !status.is_success() || parsed_result["message"].is_string() {
+        return Err(Box::new(SearchError::new(format!(
+            "SecurityTrails request failed: HTTP {}: {}",
+            status,
+            parsed_result["message"].as_str().unwrap_or("no details")
+        ))));
+    }
+
+    Ok(parsed_result)
+}
+
+// Returns bare labels, e.g. {"subdomains": ["www", "mail"]}
+pub async fn query_securitytrails_subdomains(domain: &str) -> Result<SubdomainRecord, Box<dyn Error>> {
+    let result = query_securitytrails(&format!("domain/{}/subdomains", domain), &[]).await?;
+    let labels = result["subdomains"]
+        .as_array()
+        .map(|labels| labels.iter().filter_map(|label| label.as_str()).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    Ok(SubdomainRecord::new("SecurityTrails", domain, labels))
+}
+
+// Flattens one page of historical A records into one entry per IP and period (newest
+// first), appended to history and cut at the limit. Older entries often reveal the
+// origin server from before a CDN was put in front.
+pub fn parse_securitytrails_a_history(page: &Value, history: &mut Vec<Value>, limit: Option<usize>) {
+    for record in page["records"].as_array().into_iter().flatten() {
+        for value in record["values"].as_array().into_iter().flatten() {
+            history.push(json!({
+                "ip": value["ip"],
+                "first_seen": record["first_seen"],
+                "last_seen": record["last_seen"],
+                "organizations": record["organizations"],
+            }));
+        }
+    }
+
+    if let Some(limit) = limit {
+        history.truncate(limit);
+    }
+}
+
+// Pages through the A record history up to --limit entries; one page without a limit
+pub async fn query_securitytrails_a_history(domain: &str, limit: Option<usize>) -> Result<Vec<Value>, Box<dyn Error>> {
+    let mut history = Vec::new();
+    let mut page = 1;
+
+    loop {
+        let result = query_securitytrails(
+            &format!("history/{}/dns/a", domain),
+            &[("page", page.to_string())],
+        )
+        .await?;
+
+        parse_securitytrails_a_history(&result, &mut history, limit);
+
+        let pages = result["pages"].as_u64().unwrap_or(1) as usize;
+        let limit_reached = limit.is_some_and(|limit| history.len() >= limit);
+        // Each page is a separate API call, so only page on when a limit asks for more
+        if limit.is_none() || limit_reached || page >= pages {
+            break;
+        }
+        page += 1;
+    }
+
+    Ok(history)
+}
+
+pub async fn query_securitytrails_whois(domain: &str) -> Result<Value, Box<dyn Error>> {
+    query_securitytrails(&format!("domain/{}/whois", domain), &[]).await
+}
+
+pub async fn query_securitytrails_nearby(ip: &str) -> Result<Value, Box<dyn Error>> {
+    let result = query_securitytrails(&format!("ips/nearby/{}", ip), &[]).await?;
+    Ok(result["blocks"].clone())
+}
+
+pub async fn run_single_search_securitytrails(
+    target: &str,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn Error>> {
+    let securitytrails_json = if is_ip(target) {
+        json!({
+            "ip": target,
+            "nearby": query_securitytrails_nearby(target).await?,
+        })
+    } else if is_domain(target) {
+        json!({
+            "subdomains": query_securitytrails_subdomains(target).await?,
+            "a_history": query_securitytrails_a_history(target, options.limit).await?,
+            "whois": query_securitytrails_whois(target).await?,
+        })
+    } else {
+        println!("Invalid target: {}", target);
+        return Ok(());
+    };
+
+    match output_file {
+        Some(file_path) => {
+            let mut file = open_output_file(file_path)?;
+            writeln!(file, "SecurityTrails:\n{}", serde_json::to_string_pretty(&securitytrails_json)?)?;
+        }
+        None => {
+            println!("SecurityTrails:");
+            println!("{}", serde_json::to_string_pretty(&securitytrails_json)?);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history_page(records: Value) -> Value {
+        json!({ "pages": 2, "records": records })
+    }
+
+    #[test]
+    fn a_history_has_one_entry_per_ip_and_period() {
+        let page = history_page(json!([
+            {"first_seen": "2023-01-01", "last_seen": "2024-01-01", "organizations": ["Cloudflare, Inc."],
+             "values": [{"ip": "104.16.0.1"}, {"ip": "104.16.0.2"}]},
+            {"first_seen": "2019-05-01", "last_seen": "2022-12-31", "organizations": ["Example Hosting"],
+             "values": [{"ip": "192.0.2.10"}]},
+            {"first_seen": "2018-01-01", "last_seen": "2019-04-30"}
+        ]));
+        let mut history = Vec::new();
+        parse_securitytrails_a_history(&page, &mut history, None);
+
+        let ips: Vec<&str> = history.iter().filter_map(|entry| entry["ip"].as_str()).collect();
+        assert_eq!(ips, ["104.16.0.1", "104.16.0.2", "192.0.2.10"]);
+        assert_eq!(history[2]["first_seen"], "2019-05-01");
+        assert_eq!(history[2]["organizations"], json!(["Example Hosting"]));
+    }
+
+    #[test]
+    fn a_history_is_cut_at_the_limit_across_pages() {
+        let first = history_page(json!([{"first_seen": "2023-01-01", "values": [{"ip": "192.0.2.1"}, {"ip": "192.0.2.2"}]}]));
+        let second = history_page(json!([{"first_seen": "2020-01-01", "values": [{"ip": "192.0.2.3"}, {"ip": "192.0.2.4"}]}]));
+        let mut history = Vec::new();
+        parse_securitytrails_a_history(&first, &mut history, Some(3));
+        assert_eq!(history.len(), 2);
+        parse_securitytrails_a_history(&second, &mut history, Some(3));
+        let ips: Vec<&str> = history.iter().filter_map(|entry| entry["ip"].as_str()).collect();
+        assert_eq!(ips, ["192.0.2.1", "192.0.2.2", "192.0.2.3"]);
+    }
+}
@@ -1,12 +1,17 @@
-use reqwest;
 use std::env;
 use std::error::Error;
 use std::fmt;
 use std::net::IpAddr;
 use regex::Regex;
 use serde_json::Value;
-use std::fs::File;
 use std::io::Write;
+use crate::options::SearchOptions;
+use crate::target::Network;
+use serde_json::json;
+use crate::read_list::open_output_file;
+
+// Shodan host search returns 100 matches per page, and every page costs a query credit
+const SHODAN_PAGE_SIZE: usize = 100;
 
 #[derive(Debug)]
 pub struct ShodanSearchError {
@@ -70,15 +75,19 @@ pub fn get_shodan_api_key() -> Option<String> {
 pub async fn run_single_search_shodan(
     target: &str,
     output_file: Option<&str>,
+    options: &SearchOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
     if is_ip(target) || is_domain(target) {
         let shodan_result = query_shodan(target).await?;
-        let parsed_result = serde_json::from_str::<Value>(&shodan_result)?;
+        let mut parsed_result = serde_json::from_str::<Value>(&shodan_result)?;
+        if let Some(feed) = &options.cve_feed {
+            feed.enrich_field(&mut parsed_result, "vulns", options.min_cvss)?;
+        }
 
         match output_file {
             Some(file_path) => {
-                let mut file = File::create(file_path)?;
-                write!(file, "{}", serde_json::to_string_pretty(&parsed_result)?)?;
+                let mut file = open_output_file(file_path)?;
+                writeln!(file, "Shodan:\n{}", serde_json::to_string_pretty(&parsed_result)?)?;
             }
             None => {
                 println!("Shodan:");
@@ -91,3 +100,83 @@ pub async fn run_single_search_shodan(
     Ok(())
 }
 
+
+pub fn shodan_network_query(network: &Network) -> String {
+    match network {
+        Network::Asn(asn) => format!("asn:AS{}", asn),
+        Network::Org(org) => format!("org:\"{}\"", org.replace('"', "")),
+    }
+}
+
+pub async fn query_shodan_search(query: &str, page: usize) -> Result<Value, Box<dyn Error>> {
+    let shodan_api_key = get_shodan_api_key().expect("SHODAN_API not found");
+
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get("https://api.shodan.io/shodan/host/search")
+        .query(&[
+            ("key", shodan_api_key),
+            ("query", query.to_string()),
+            ("page", page.to_string()),
+        ])
+        .send()
+        .await
+        .map_err(|err| ShodanSearchError::new(err.to_string()))?;
+
+    let response_body = response
+        .text()
+        .await
+        .map_err(|err| ShodanSearchError::new(err.to_string()))?;
+
+    Ok(serde_json::from_str(&response_body)?)
+}
+
+// Pages through the host search up to --limit matches; one page without a limit
+pub async fn run_network_search_shodan(
+    network: &Network,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let query = shodan_network_query(network);
+    let wanted = options.limit.unwrap_or(SHODAN_PAGE_SIZE).max(1);
+    let mut matches: Vec<Value> = Vec::new();
+    let mut total = 0;
+    let mut page = 1;
+
+    loop {
+        let page_result = query_shodan_search(&query, page).await?;
+        if let Some(error) = page_result["error"].as_str() {
+            return Err(Box::new(ShodanSearchError::new(error.to_string())));
+        }
+
+        let page_matches = page_result["matches"].as_array().cloned().unwrap_or_default();
+        total = page_result["total"].as_u64().unwrap_or(0) as usize;
+        let page_was_empty = page_matches.is_empty();
+        matches.extend(page_matches);
+
+        if page_was_empty || matches.len() >= wanted || matches.len() >= total {
+            break;
+        }
+        page += 1;
+    }
+    matches.truncate(wanted);
+
+    let parsed_result = json!({
+        "query": query,
+        "total": total,
+        "matches": matches,
+    });
+
+    match output_file {
+        Some(file_path) => {
+            let mut file = open_output_file(file_path)?;
+            writeln!(file, "Shodan:\n{}", serde_json::to_string_pretty(&parsed_result)?)?;
+        }
+        None => {
+            println!("Shodan:");
+            println!("{}", serde_json::to_string_pretty(&parsed_result)?);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use regex::Regex;
+use reqwest::Url;
+use std::net::IpAddr;
+use std::sync::OnceLock;
+
+// What kind of thing the user asked us to look up, which decides the providers it goes to
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    Ip(IpAddr),
+    Domain(String),
+    // The domain is kept alongside so the email can be pivoted to its owning domain
+    Email { address: String, domain: String },
+    // MD5, SHA1 or SHA256 file hash, stored lowercase
+    Hash(String),
+    // http(s) URL plus the host it points at, which goes to the domain or IP providers
+    Url { url: String, host: String },
+    Network(Network),
+    // IPv4 or IPv6 network in CIDR notation, e.g. 192.0.2.0/24
+    Cidr(String),
+}
+
+// Targets that stand for many hosts, enumerated through each provider's search syntax
+#[derive(Debug, Clone, PartialEq)]
+pub enum Network {
+    Asn(u32),
+    Org(String),
+}
+
+// Accepts AS13335 or as13335
+pub fn parse_asn(target: &str) -> Option<u32> {
+    let digits = target.strip_prefix("AS").or_else(|| target.strip_prefix("as"))?;
+    digits.parse().ok()
+}
+
+pub fn is_hash(target: &str) -> bool {
+    matches!(target.len(), 32 | 40 | 64) && target.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// Any number of labels, so subdomains such as www.example.co.uk are domain targets too
+pub fn is_domain(target: &str) -> bool {
+    static DOMAIN_REGEX: OnceLock<Regex> = OnceLock::new();
+    DOMAIN_REGEX
+        .get_or_init(|| Regex::new(r"^(?:[a-zA-Z0-9](?:[a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?\.)+[a-zA-Z]{2,}$").unwrap())
+        .is_match(target)
+}
+
+impl Target {
+    pub fn parse(target: &str) -> Option<Target> {
+        let target = target.trim();
+
+        if let Ok(ip) = target.parse::<IpAddr>() {
+            return Some(Target::Ip(ip));
+        }
+
+        if target.starts_with("http://") || target.starts_with("https://") {
+            let parsed = Url::parse(target).ok()?;
+            // IPv6 hosts come back bracketed, e.g. [2001:db8::1]
+            let host = parsed.host_str()?.trim_start_matches('[').trim_end_matches(']').to_lowercase();
+            return Some(Target::Url {
+                url: target.to_string(),
+                host,
+            });
+        }
+
+        if let Some((network, prefix)) = target.split_once('/') {
+            let max_prefix = match network.parse::<IpAddr>() {
+                Ok(IpAddr::V4(_)) => 32,
+                Ok(IpAddr::V6(_)) => 128,
+                Err(_) => return None,
+            };
+            return match prefix.parse::<u8>() {
+                Ok(prefix) if prefix <= max_prefix => Some(Target::Cidr(target.to_string())),
+                _ => None,
+            };
+        }
+
+        if let Some(asn) = parse_asn(target) {
+            return Some(Target::Network(Network::Asn(asn)));
+        }
+
+        if is_hash(target) {
+            return Some(Target::Hash(target.to_lowercase()));
+        }
+
+        if is_domain(target) {
+            return Some(Target::Domain(target.to_string()));
+        }
+
+        if let Some((user, domain)) = target.rsplit_once('@') {
+            let valid_user = !user.is_empty() && !user.contains(|c: char| c.is_whitespace() || c == '@');
+            if valid_user && is_domain(domain) {
+                return Some(Target::Email {
+                    address: target.to_lowercase(),
+                    domain: domain.to_lowercase(),
+                });
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_domains_with_any_number_of_labels() {
+        assert_eq!(Target::parse("example.com"), Some(Target::Domain("example.com".to_string())));
+        assert_eq!(Target::parse("www.example.co.uk"), Some(Target::Domain("www.example.co.uk".to_string())));
+        assert_eq!(Target::parse("a.b.c.example.com.au"), Some(Target::Domain("a.b.c.example.com.au".to_string())));
+        assert_eq!(Target::parse("-bad.example.com"), None);
+        assert_eq!(Target::parse("localhost"), None);
+    }
+
+    #[test]
+    fn parses_emails_with_their_domain() {
+        assert_eq!(
+            Target::parse("Alice@Mail.Example.co.uk"),
+            Some(Target::Email {
+                address: "alice@mail.example.co.uk".to_string(),
+                domain: "mail.example.co.uk".to_string(),
+            })
+        );
+        assert_eq!(Target::parse("@example.com"), None);
+    }
+
+    #[test]
+    fn parses_hashes_before_domains() {
+        let md5 = "44D88612FEA8A8F36DE82E1278ABB02F";
+        assert_eq!(Target::parse(md5), Some(Target::Hash(md5.to_lowercase())));
+        let sha256 = "a".repeat(64);
+        assert_eq!(Target::parse(&sha256), Some(Target::Hash(sha256.clone())));
+        assert_eq!(Target::parse(&"a".repeat(33)), None);
+    }
+
+    #[test]
+    fn parses_urls_and_their_host() {
+        assert_eq!(
+            Target::parse("https://Login.Example.com/path?q=1"),
+            Some(Target::Url {
+                url: "https://Login.Example.com/path?q=1".to_string(),
+                host: "login.example.com".to_string(),
+            })
+        );
+        assert_eq!(
+            Target::parse("http://[2001:db8::1]:8080/"),
+            Some(Target::Url {
+                url: "http://[2001:db8::1]:8080/".to_string(),
+                host: "2001:db8::1".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn parses_ips_cidrs_and_asns() {
+        assert_eq!(Target::parse(" 192.0.2.1 "), Some(Target::Ip("192.0.2.1".parse().unwrap())));
+        assert_eq!(Target::parse("192.0.2.0/24"), Some(Target::Cidr("192.0.2.0/24".to_string())));
+        assert_eq!(Target::parse("2001:db8::/32"), Some(Target::Cidr("2001:db8::/32".to_string())));
+        assert_eq!(Target::parse("192.0.2.0/33"), None);
+        assert_eq!(Target::parse("example.com/24"), None);
+        assert_eq!(Target::parse("AS13335"), Some(Target::Network(Network::Asn(13335))));
+        assert_eq!(Target::parse("as64500"), Some(Target::Network(Network::Asn(64500))));
+    }
+}
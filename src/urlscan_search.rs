@@ -0,0 +1,292 @@
+use reqwest::{Client, StatusCode};
+use std::env;
+use std::error::Error;
+use std::io::Write;
+use std::net::IpAddr;
+use std::time::Duration;
+use tokio::task::JoinSet;
+use serde_json::{json, Value};
+use crate::model::{value_to_string, WebScanRecord};
+use crate::options::SearchOptions;
+use crate::error::SearchError;
+use crate::target::is_domain;
+use crate::read_list::open_output_file;
+
+// Results per search request when no --limit is given
+const URLSCAN_DEFAULT_SIZE: usize = 10;
+// How long a submitted scan is polled for before giving up
+const URLSCAN_SUBMIT_ATTEMPTS: usize = 12;
+// Scan results fetched at once after a search
+const URLSCAN_RESULT_CONCURRENCY: usize = 4;
+
+pub fn is_url(target: &str) -> bool {
+    target.starts_with("http://") || target.starts_with("https://")
+}
+
+pub fn is_ip(target: &str) -> bool {
+    target.parse::<IpAddr>().is_ok()
+}
+
+// Searching works without a key at lower rate limits, so URLSCAN_API is optional.
+// Submitting scans always needs it.
+pub fn get_urlscan_api_key() -> Option<String> {
+    env::var("URLSCAN_API").ok()
+}
+
+pub async fn query_urlscan_search(query: &str, size: usize) -> Result<Value, Box<dyn Error>> {
+    let client = Client::new();
+
+    let mut request = client
+        .get("https://urlscan.io/api/v1/search/")
+        .query(&[("q", query.to_string()), ("size", size.to_string())]);
+    if let Some(api_key) = get_urlscan_api_key() {
+        request = request.header("API-Key", api_key);
+    }
+
+    let response = request.send().await?;
+    let response_body = response.text().await?;
+
+    Ok(serde_json::from_str(&response_body)?)
+}
+
+// None while a scan is still running (urlscan answers 404 until the result is ready)
+pub async fn query_urlscan_result(uuid: &str) -> Result<Option<Value>, Box<dyn Error>> {
+    let client = Client::new();
+
+    let url = format!("https://urlscan.io/api/v1/result/{}/", uuid);
+
+    let mut request = client.get(&url);
+    if let Some(api_key) = get_urlscan_api_key() {
+        request = request.header("API-Key", api_key);
+    }
+
+    let response = request.send().await?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    let response_body = response.text().await?;
+    Ok(Some(serde_json::from_str(&response_body)?))
+}
+
+pub async fn submit_urlscan(url: &str, visibility: &str) -> Result<Value, Box<dyn Error>> {
+    let api_key = get_urlscan_api_key().expect("URLSCAN_API not found");
+
+    let client = Client::new();
+
+    let response = client
+        .post("https://urlscan.io/api/v1/scan/")
+        .header("API-Key", api_key)
+        .json(&json!({ "url": url, "visibility": visibility }))
+        .send()
+        .await?;
+
+    let status = response.status();
+    let submission: Value = serde_json::from_str(&response.text().await?)?;
+    if !status.is_success() {
+        return Err(Box::new(SearchError::new(format!(
+            "urlscan.io rejected the submission: {}",
+            submission["message"].as_str().unwrap_or(status.as_str())
+        ))));
+    }
+
+    Ok(submission)
+}
+
+// Quotes and backslashes have to be escaped inside an Elasticsearch phrase
+fn quote_query_value(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+pub fn urlscan_query_for(target: &str) -> Option<String> {
+    if is_url(target) {
+        let url = quote_query_value(target);
+        Some(format!("page.url:{} OR task.url:{}", url, url))
+    } else if is_ip(target) {
+        Some(format!("ip:{}", quote_query_value(target)))
+    } else if is_domain(target) {
+        Some(format!("domain:{}", quote_query_value(target)))
+    } else {
+        None
+    }
+}
+
+// Redirects are the requests whose redirectResponse led to the next hop
+pub fn parse_urlscan_result(uuid: &str, result: &Value) -> WebScanRecord {
+    let page = &result["page"];
+    let task = &result["task"];
+
+    let redirects = result["data"]["requests"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|request| request["request"]["redirectResponse"]["url"].as_str())
+        .map(String::from)
+        .collect();
+
+    let contacted_domains = result["lists"]["domains"]
+        .as_array()
+        .map(|domains| domains.iter().filter_map(value_to_string).collect())
+        .unwrap_or_default();
+
+    WebScanRecord {
+        source: "urlscan.io".to_string(),
+        uuid: uuid.to_string(),
+        url: value_to_string(&task["url"]),
+        final_url: value_to_string(&page["url"]),
+        title: value_to_string(&page["title"]),
+        server: value_to_string(&page["server"]),
+        ip: value_to_string(&page["ip"]),
+        asn: value_to_string(&page["asn"]),
+        asn_name: value_to_string(&page["asnname"]),
+        scanned_at: value_to_string(&task["time"]),
+        screenshot: value_to_string(&task["screenshotURL"]),
+        redirects,
+        contacted_domains,
+    }
+}
+
+// Scans come back newest first; each one's full result is fetched for the details, a few
+// at a time. A result that fails or has been deleted is skipped rather than failing the search.
+pub async fn search_urlscan(query: &str, limit: usize) -> Result<(Value, Vec<WebScanRecord>), Box<dyn Error>> {
+    let search = query_urlscan_search(query, limit).await?;
+    let uuids: Vec<String> = search["results"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|hit| hit["_id"].as_str().map(String::from))
+        .take(limit)
+        .collect();
+
+    let mut tasks = JoinSet::new();
+    let mut scans: Vec<(usize, WebScanRecord)> = Vec::new();
+    for (index, uuid) in uuids.into_iter().enumerate() {
+        if tasks.len() >= URLSCAN_RESULT_CONCURRENCY {
+            if let Some(joined) = tasks.join_next().await {
+                collect_urlscan_result(joined?, &mut scans);
+            }
+        }
+        tasks.spawn(async move {
+            let result = query_urlscan_result(&uuid).await.map_err(|err| err.to_string());
+            (index, uuid, result)
+        });
+    }
+    while let Some(joined) = tasks.join_next().await {
+        collect_urlscan_result(joined?, &mut scans);
+    }
+
+    scans.sort_by_key(|(index, _)| *index);
+    Ok((search["total"].clone(), scans.into_iter().map(|(_, scan)| scan).collect()))
+}
+
+fn collect_urlscan_result(
+    (index, uuid, result): (usize, String, Result<Option<Value>, String>),
+    scans: &mut Vec<(usize, WebScanRecord)>,
+) {
+    match result {
+        Ok(Some(result)) => scans.push((index, parse_urlscan_result(&uuid, &result))),
+        Ok(None) => println!("urlscan.io result {} is no longer available", uuid),
+        Err(err) => println!("urlscan.io result {} could not be fetched: {}", uuid, err),
+    }
+}
+
+// Submits a new scan and waits for it to finish. Only runs with --urlscan-submit,
+// since it sends the target to urlscan.io and may publish it.
+pub async fn submit_and_wait_urlscan(url: &str, visibility: &str) -> Result<Value, Box<dyn Error>> {
+    let submission = submit_urlscan(url, visibility).await?;
+    let uuid = submission["uuid"]
+        .as_str()
+        .ok_or_else(|| SearchError::new("urlscan.io returned no scan id".to_string()))?
+        .to_string();
+
+    for _ in 0..URLSCAN_SUBMIT_ATTEMPTS {
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        if let Some(result) = query_urlscan_result(&uuid).await? {
+            return Ok(serde_json::to_value(parse_urlscan_result(&uuid, &result))?);
+        }
+    }
+
+    Ok(json!({
+        "uuid": uuid,
+        "result": submission["result"],
+        "message": "Scan still running, check the result link later",
+    }))
+}
+
+pub async fn run_single_search_urlscan(
+    target: &str,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn Error>> {
+    let query = match urlscan_query_for(target) {
+        Some(query) => query,
+        None => {
+            println!("Invalid target: {}", target);
+            return Ok(());
+        }
+    };
+
+    let (total, scans) = search_urlscan(&query, options.limit.unwrap_or(URLSCAN_DEFAULT_SIZE).max(1)).await?;
+    let mut urlscan_json = json!({
+        "target": target,
+        "total": total,
+        "scans": scans,
+    });
+
+    if options.urlscan_submit && !is_ip(target) {
+        let submit_url = if is_url(target) {
+            target.to_string()
+        } else {
+            format!("https://{}", target)
+        };
+        urlscan_json["submitted"] = submit_and_wait_urlscan(&submit_url, &options.urlscan_visibility).await?;
+    }
+
+    match output_file {
+        Some(file_path) => {
+            let mut file = open_output_file(file_path)?;
+            writeln!(file, "urlscan.io:\n{}", serde_json::to_string_pretty(&urlscan_json)?)?;
+        }
+        None => {
+            println!("urlscan.io:");
+            println!("{}", serde_json::to_string_pretty(&urlscan_json)?);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queries_are_quoted_per_target_kind() {
+        assert_eq!(urlscan_query_for("example.com").unwrap(), r#"domain:"example.com""#);
+        assert_eq!(urlscan_query_for("2001:db8::1").unwrap(), r#"ip:"2001:db8::1""#);
+        assert_eq!(
+            urlscan_query_for(r#"https://example.com/a"b"#).unwrap(),
+            r#"page.url:"https://example.com/a\"b" OR task.url:"https://example.com/a\"b""#
+        );
+        assert_eq!(urlscan_query_for("not a target"), None);
+    }
+
+    #[test]
+    fn result_details_are_extracted() {
+        let result = json!({
+            "task": {"url": "http://example.com/", "time": "2024-01-01T00:00:00Z", "screenshotURL": "https://urlscan.io/screenshots/abc.png"},
+            "page": {"url": "https://www.example.com/", "title": "Example", "server": "nginx", "ip": "192.0.2.1", "asn": "AS64500", "asnname": "EXAMPLE-AS"},
+            "data": {"requests": [
+                {"request": {"redirectResponse": {"url": "http://example.com/"}}},
+                {"request": {}}
+            ]},
+            "lists": {"domains": ["www.example.com", "cdn.example.net"]}
+        });
+        let scan = parse_urlscan_result("abc", &result);
+        assert_eq!(scan.final_url.as_deref(), Some("https://www.example.com/"));
+        assert_eq!(scan.title.as_deref(), Some("Example"));
+        assert_eq!(scan.asn.as_deref(), Some("AS64500"));
+        assert_eq!(scan.redirects, ["http://example.com/"]);
+        assert_eq!(scan.contacted_domains, ["www.example.com", "cdn.example.net"]);
+        assert_eq!(scan.screenshot.as_deref(), Some("https://urlscan.io/screenshots/abc.png"));
+    }
+}
@@ -1,46 +1,331 @@
-extern crate virustotal;
-use virustotal::VtClient;
+use reqwest::Client;
 use std::env;
-use serde_json::Value;
-use std::fs::File;
-use std::io::Write;
 use std::error::Error;
+use std::io::Write;
+use serde_json::Value;
+use crate::model::{value_to_string, AnalysisStats, IndicatorReport, RelatedIndicator};
+use crate::options::SearchOptions;
+use crate::read_list::open_output_file;
+use crate::error::SearchError;
+use crate::target::{is_domain, is_hash};
+
+// Number of related objects fetched per relationship for a plain report
+const VT_RELATIONSHIP_LIMIT: usize = 10;
+// Largest page the relationship endpoints accept
+const VT_RELATIONSHIP_PAGE_SIZE: usize = 40;
+// Entries collected per --vt-relations relationship when no --limit is given (5 pages)
+const VT_RELATIONSHIP_MAX: usize = 200;
 
 pub fn is_ip(target: &str) -> bool {
     target.parse::<std::net::IpAddr>().is_ok()
 }
 
-pub fn is_domain(target: &str) -> bool {
-    let domain_regex = regex::Regex::new(r"^(?:[a-zA-Z0-9](?:[a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?\.)+[a-zA-Z]{2,}$").unwrap();
-    domain_regex.is_match(target)
+pub fn is_url(target: &str) -> bool {
+    target.starts_with("http://") || target.starts_with("https://")
 }
 
 pub fn get_vt_api_key() -> Option<String> {
     env::var("VT_API").ok()
 }
 
+// The v3 collection an indicator lives in, plus the id used in its path
+pub fn vt_object_path(target: &str) -> Option<(&'static str, String)> {
+    if is_ip(target) {
+        Some(("ip_addresses", target.to_string()))
+    } else if is_url(target) {
+        // URL ids are the unpadded url-safe base64 of the URL itself
+        Some(("urls", base64::encode_config(target, base64::URL_SAFE_NO_PAD)))
+    } else if is_hash(target) {
+        Some(("files", target.to_lowercase()))
+    } else if is_domain(target) {
+        Some(("domains", target.to_lowercase()))
+    } else {
+        None
+    }
+}
+
+pub async fn query_virustotal(path: &str, params: &[(&str, String)]) -> Result<Value, Box<dyn Error>> {
+    let api_key = get_vt_api_key().expect("VT_API not found");
+
+    let url = format!("https://www.virustotal.com/api/v3/{}", path);
+
+    let client = Client::new();
+
+    let response = client
+        .get(&url)
+        .query(params)
+        .header("x-apikey", api_key)
+        .send()
+        .await
+        .map_err(|err| SearchError::new(err.to_string()))?;
+
+    let response_body = response
+        .text()
+        .await
+        .map_err(|err| SearchError::new(err.to_string()))?;
+
+    let parsed_result: Value = serde_json::from_str(&response_body)?;
+
+    // Errors come back as {"error": {"code": "NotFoundError", "message": "..."}}
+    if let Some(error) = parsed_result.get("error") {
+        return Err(Box::new(SearchError::new(format!(
+            "{}: {}",
+            error["code"].as_str().unwrap_or("Error"),
+            error["message"].as_str().unwrap_or("")
+        ))));
+    }
+
+    Ok(parsed_result)
+}
+
+pub async fn query_virustotal_object(collection: &str, id: &str) -> Result<Value, Box<dyn Error>> {
+    query_virustotal(&format!("{}/{}", collection, id), &[]).await
+}
+
+// One page of a relationship such as /domains/{id}/resolutions
+pub async fn query_virustotal_relationship(
+    collection: &str,
+    id: &str,
+    relationship: &str,
+    limit: usize,
+    cursor: Option<&str>,
+) -> Result<Value, Box<dyn Error>> {
+    let mut params = vec![("limit", limit.to_string())];
+    if let Some(cursor) = cursor {
+        params.push(("cursor", cursor.to_string()));
+    }
+
+    query_virustotal(&format!("{}/{}/{}", collection, id, relationship), &params).await
+}
+
+pub fn parse_virustotal_object(target: &str, kind: &str, object: &Value) -> IndicatorReport {
+    let attributes = &object["data"]["attributes"];
+    let mut report = IndicatorReport::new("VirusTotal", target, kind);
+
+    report.reputation = attributes["reputation"].as_i64();
+
+    let stats = &attributes["last_analysis_stats"];
+    if stats.is_object() {
+        let count = |key: &str| stats[key].as_u64().unwrap_or(0);
+        report.last_analysis_stats = Some(AnalysisStats {
+            malicious: count("malicious"),
+            suspicious: count("suspicious"),
+            harmless: count("harmless"),
+            undetected: count("undetected"),
+            timeout: count("timeout"),
+        });
+    }
+
+    if let Some(categories) = attributes["categories"].as_object() {
+        for (engine, category) in categories {
+            if let Some(category) = category.as_str() {
+                report.categories.insert(engine.clone(), category.to_string());
+            }
+        }
+    }
+
+    report.tags = attributes["tags"]
+        .as_array()
+        .map(|tags| tags.iter().filter_map(value_to_string).collect())
+        .unwrap_or_default();
+
+    let detail_keys: &[&str] = match kind {
+        "ip" => &["as_owner", "asn", "country", "network"],
+        "domain" => &["registrar", "creation_date", "last_dns_records_date"],
+        "url" => &["title", "last_final_url", "last_http_response_code"],
+        "file" => &["meaningful_name", "type_description", "size", "sha256"],
+        _ => &[],
+    };
+    for key in detail_keys {
+        if !attributes[*key].is_null() {
+            report.details.insert(key.to_string(), attributes[*key].clone());
+        }
+    }
+
+    if kind == "file" {
+        parse_virustotal_file_details(attributes, &mut report);
+    }
+
+    report
+}
+
+// Engine verdicts, submitted names, first submission and Authenticode signature info
+fn parse_virustotal_file_details(attributes: &Value, report: &mut IndicatorReport) {
+    if let Some(results) = attributes["last_analysis_results"].as_object() {
+        let detections: serde_json::Map<String, Value> = results
+            .iter()
+            .filter(|(_, result)| matches!(result["category"].as_str(), Some("malicious") | Some("suspicious")))
+            .map(|(engine, result)| (engine.clone(), result["result"].clone()))
+            .collect();
+        report.details.insert("detections".to_string(), Value::Object(detections));
+    }
+
+    if attributes["names"].is_array() {
+        report.details.insert("names".to_string(), attributes["names"].clone());
+    }
+
+    if let Some(first_submission) = attributes["first_submission_date"].as_i64() {
+        report.details.insert("first_submission_date".to_string(), Value::from(first_submission));
+    }
+
+    if attributes["signature_info"].is_object() {
+        report.details.insert("signature_info".to_string(), attributes["signature_info"].clone());
+    }
+}
+
+// Maps relationship objects onto related indicators, e.g. resolutions become
+// the domains an IP served (or the IPs a domain resolved to)
+pub fn parse_virustotal_related(relationship: &str, kind: &str, page: &Value) -> Vec<RelatedIndicator> {
+    let mut related = Vec::new();
+
+    for object in page["data"].as_array().into_iter().flatten() {
+        let attributes = &object["attributes"];
+        let (related_kind, value) = match object["type"].as_str().unwrap_or("") {
+            "resolution" if kind == "ip" => ("domain", attributes["host_name"].as_str()),
+            "resolution" => ("ip", attributes["ip_address"].as_str()),
+            "file" => ("file", object["id"].as_str()),
+            "domain" => ("domain", object["id"].as_str()),
+            "ip_address" => ("ip", object["id"].as_str()),
+            "url" => ("url", attributes["url"].as_str()),
+            _ => continue,
+        };
+
+        if let Some(value) = value {
+            related.push(RelatedIndicator {
+                relation: relationship.to_string(),
+                kind: related_kind.to_string(),
+                value: value.to_string(),
+                // Resolution dates are unix timestamps
+                date: attributes["date"].as_i64().map(|date| date.to_string()),
+            });
+        }
+    }
+
+    related
+}
+
+fn report_kind(collection: &str) -> &'static str {
+    match collection {
+        "ip_addresses" => "ip",
+        "domains" => "domain",
+        "urls" => "url",
+        _ => "file",
+    }
+}
+
+// Network contacts of a file, always fetched since they are offered as pivots
+const FILE_RELATIONSHIPS: [&str; 2] = ["contacted_ips", "contacted_domains"];
+
+fn supports_relationship(collection: &str, relationship: &str) -> bool {
+    match collection {
+        "domains" => true,
+        "ip_addresses" => relationship != "subdomains",
+        _ => false,
+    }
+}
+
+// Follows meta.cursor page by page, stopping at --limit entries (VT_RELATIONSHIP_MAX without one)
+pub async fn collect_virustotal_relationship(
+    collection: &str,
+    id: &str,
+    kind: &str,
+    relationship: &str,
+    limit: Option<usize>,
+) -> Result<Vec<RelatedIndicator>, Box<dyn Error>> {
+    let limit = limit.unwrap_or(VT_RELATIONSHIP_MAX).max(1);
+    let mut related = Vec::new();
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let page_size = VT_RELATIONSHIP_PAGE_SIZE.min(limit - related.len());
+        let page = query_virustotal_relationship(collection, id, relationship, page_size, cursor.as_deref()).await?;
+        related.extend(parse_virustotal_related(relationship, kind, &page));
+
+        cursor = page["meta"]["cursor"].as_str().filter(|c| !c.is_empty()).map(String::from);
+        if cursor.is_none() || related.len() >= limit {
+            break;
+        }
+    }
+
+    related.truncate(limit);
+    Ok(related)
+}
+
+// Relationship lookups are best-effort: a rate limit or a relationship the key's plan
+// doesn't cover is reported, and the object report is kept without it
+fn skip_relationship(relationship: &str, target: &str, err: Box<dyn Error>) {
+    println!("VirusTotal {} for {} unavailable: {}", relationship, target, err);
+}
+
+// Each relationship costs a request against the quota, so IP and domain targets only fetch
+// the ones asked for with --vt-relations. File targets always fetch their contacted hosts.
+pub async fn search_virustotal(target: &str, options: &SearchOptions) -> Result<IndicatorReport, Box<dyn Error>> {
+    let (collection, id) = vt_object_path(target)
+        .ok_or_else(|| SearchError::new(format!("Invalid target: {}", target)))?;
+    let kind = report_kind(collection);
+
+    let object = query_virustotal_object(collection, &id).await?;
+    let mut report = parse_virustotal_object(target, kind, &object);
+
+    if collection == "files" {
+        for relationship in FILE_RELATIONSHIPS {
+            match query_virustotal_relationship(collection, &id, relationship, VT_RELATIONSHIP_LIMIT, None).await {
+                Ok(page) => report.related.extend(parse_virustotal_related(relationship, kind, &page)),
+                Err(err) => skip_relationship(relationship, target, err),
+            }
+        }
+        return Ok(report);
+    }
+    if collection == "urls" {
+        return Ok(report);
+    }
+
+    for relationship in &options.vt_relations {
+        if !supports_relationship(collection, relationship) {
+            println!("VirusTotal relationship {} is not available for {} targets", relationship, kind);
+            continue;
+        }
+        let related = match collect_virustotal_relationship(collection, &id, kind, relationship, options.limit).await {
+            Ok(related) => related,
+            Err(err) => {
+                skip_relationship(relationship, target, err);
+                continue;
+            }
+        };
+        for indicator in related {
+            if !report.related.contains(&indicator) {
+                report.related.push(indicator);
+            }
+        }
+    }
+
+    Ok(report)
+}
+
 pub async fn run_single_search_virustotal(
     target: &str,
     output_file: Option<&str>,
+    options: &SearchOptions,
 ) -> Result<(), Box<dyn Error>> {
-    let api_key = get_vt_api_key().expect("VT_API not found");
-    let vt = VtClient::new(&api_key);
+    let report = search_virustotal(target, options).await?;
+    write_virustotal_report(&report, output_file, options)
+}
 
-    let vt_result: String = if is_ip(target) {
-        let ip_report = vt.report_ip_address(target);
-        format!("IP Report: {:?}", ip_report)  // Replace with actual fields
-    } else if is_domain(target) {
-        let domain_report = vt.repot_domain(target);  // Corrected the typo
-        format!("Domain Report: {:?}", domain_report)  // Replace with actual fields
+pub fn write_virustotal_report(
+    report: &IndicatorReport,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn Error>> {
+    let vt_result = if options.summary {
+        report.summary_line()
     } else {
-        return Err(Box::new(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid target")));
+        serde_json::to_string_pretty(report)?
     };
-    
 
     match output_file {
         Some(file_path) => {
-            let mut file = File::create(file_path)?;
-            write!(file, "{}", vt_result)?;
+            let mut file = open_output_file(file_path)?;
+            writeln!(file, "VirusTotal:\n{}", vt_result)?;
         },
         None => {
             println!("VirusTotal:");
@@ -0,0 +1,244 @@
+use reqwest::{Client, Url};
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::io::Write;
+use serde_json::Value;
+use crate::model::UrlHistoryRecord;
+use crate::options::SearchOptions;
+use crate::error::SearchError;
+use crate::target::is_domain;
+use crate::read_list::open_output_file;
+
+// Captures per CDX request; without --limit only the first page is fetched
+const WAYBACK_PAGE_SIZE: usize = 5000;
+
+// The resume key comes back already URL-encoded, so it is appended verbatim;
+// encoding it again would make CDX restart from the first page
+pub fn cdx_url(domain: &str, page_size: usize, resume_key: Option<&str>) -> Result<Url, Box<dyn Error>> {
+    let mut url = Url::parse_with_params(
+        "https://web.archive.org/cdx/search/cdx",
+        &[
+            ("url", format!("*.{}/*", domain)),
+            ("output", "json".to_string()),
+            ("collapse", "urlkey".to_string()),
+            ("fl", "original".to_string()),
+            ("limit", page_size.to_string()),
+            ("showResumeKey", "true".to_string()),
+        ],
+    )?;
+    if let Some(resume_key) = resume_key {
+        let query = format!("{}&resumeKey={}", url.query().unwrap_or(""), resume_key);
+        url.set_query(Some(&query));
+    }
+
+    Ok(url)
+}
+
+// One page of captures plus the resume key for the next one, if any
+pub async fn query_wayback_cdx(
+    domain: &str,
+    page_size: usize,
+    resume_key: Option<&str>,
+) -> Result<(Vec<String>, Option<String>), Box<dyn Error>> {
+    let url = cdx_url(domain, page_size, resume_key)?;
+
+    let response = Client::new()
+        .get(url)
+        .send()
+        .await
+        .map_err(|err| SearchError::new(err.to_string()))?;
+
+    // Rate limits and outages come back as HTML error pages
+    let status = response.status();
+    if !status.is_success() {
+        return Err(Box::new(SearchError::new(format!("Wayback CDX request failed: HTTP {}", status))));
+    }
+
+    let response_body = response
+        .text()
+        .await
+        .map_err(|err| SearchError::new(err.to_string()))?;
+
+    parse_cdx_page(&response_body)
+}
+
+// Rows are ["original"] after a header row; with showResumeKey the key follows an empty
+// row. Domains with no captures get an empty body rather than an empty JSON array.
+pub fn parse_cdx_page(response_body: &str) -> Result<(Vec<String>, Option<String>), Box<dyn Error>> {
+    if response_body.trim().is_empty() {
+        return Ok((Vec::new(), None));
+    }
+    let rows: Vec<Vec<String>> = serde_json::from_str::<Value>(response_body)?
+        .as_array()
+        .map(|rows| {
+            rows.iter()
+                .map(|row| row.as_array().into_iter().flatten().filter_map(|v| v.as_str().map(String::from)).collect())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut urls = Vec::new();
+    let mut next_key = None;
+    let mut after_blank = false;
+    for row in rows.into_iter().skip(1) {
+        match row.first() {
+            None => after_blank = true,
+            Some(value) if after_blank => next_key = Some(value.clone()),
+            Some(value) => urls.push(value.clone()),
+        }
+    }
+
+    Ok((urls, next_key))
+}
+
+// Hosts, query parameter names and file extensions across the captured URLs,
+// counting how many URLs use each parameter and extension
+pub fn parse_wayback_urls(domain: &str, urls: Vec<String>) -> UrlHistoryRecord {
+    let urls: BTreeSet<String> = urls.into_iter().collect();
+    let mut record = UrlHistoryRecord {
+        source: "Wayback Machine".to_string(),
+        domain: domain.to_string(),
+        ..Default::default()
+    };
+
+    for url in &urls {
+        let parsed = match Url::parse(url) {
+            Ok(parsed) => parsed,
+            Err(_) => continue,
+        };
+
+        if let Some(host) = parsed.host_str() {
+            record.hosts.insert(host.to_lowercase());
+        }
+
+        let names: BTreeSet<String> = parsed.query_pairs().map(|(name, _)| name.into_owned()).collect();
+        for name in names {
+            *record.parameters.entry(name).or_insert(0) += 1;
+        }
+
+        let file_name = parsed.path_segments().and_then(|mut segments| segments.next_back()).unwrap_or("");
+        if let Some((_, extension)) = file_name.rsplit_once('.') {
+            if !extension.is_empty() && extension.chars().all(|c| c.is_ascii_alphanumeric()) {
+                *record.extensions.entry(extension.to_lowercase()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    record.urls = urls.into_iter().collect();
+    record
+}
+
+// Follows the resume key page by page up to --limit URLs
+pub async fn search_wayback(domain: &str, limit: Option<usize>) -> Result<UrlHistoryRecord, Box<dyn Error>> {
+    let wanted = limit.unwrap_or(WAYBACK_PAGE_SIZE).max(1);
+    let mut urls: Vec<String> = Vec::new();
+    let mut resume_key: Option<String> = None;
+
+    loop {
+        let page_size = WAYBACK_PAGE_SIZE.min(wanted - urls.len());
+        let (page_urls, next_key) = query_wayback_cdx(domain, page_size, resume_key.as_deref()).await?;
+        let page_was_empty = page_urls.is_empty();
+        urls.extend(page_urls);
+
+        resume_key = next_key;
+        if page_was_empty || resume_key.is_none() || urls.len() >= wanted {
+            break;
+        }
+    }
+    urls.truncate(wanted);
+
+    Ok(parse_wayback_urls(domain, urls))
+}
+
+pub async fn run_single_search_wayback(
+    target: &str,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn Error>> {
+    if !is_domain(target) {
+        println!("Invalid target: {}", target);
+        return Ok(());
+    }
+
+    let record = search_wayback(&target.to_lowercase(), options.limit).await?;
+    let wayback_result = if options.summary {
+        record.summary_line()
+    } else {
+        serde_json::to_string_pretty(&record)?
+    };
+
+    match output_file {
+        Some(file_path) => {
+            let mut file = open_output_file(file_path)?;
+            writeln!(file, "Wayback Machine:\n{}", wayback_result)?;
+        }
+        None => {
+            println!("Wayback Machine:");
+            println!("{}", wayback_result);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cdx_page_splits_urls_from_the_resume_key() {
+        let body = r#"[["original"],
+            ["http://example.com/"],
+            ["https://www.example.com/login.php?next=%2F"],
+            [],
+            ["com%2Cexample%2Cwww%29%2Flogin.php%3Fnext%3D%252F+20200101000000"]]"#;
+        let (urls, key) = parse_cdx_page(body).unwrap();
+        assert_eq!(urls, ["http://example.com/", "https://www.example.com/login.php?next=%2F"]);
+        assert_eq!(key.as_deref(), Some("com%2Cexample%2Cwww%29%2Flogin.php%3Fnext%3D%252F+20200101000000"));
+    }
+
+    #[test]
+    fn cdx_page_handles_the_last_page_and_empty_bodies() {
+        let (urls, key) = parse_cdx_page(r#"[["original"],["http://example.com/a.js"]]"#).unwrap();
+        assert_eq!(urls, ["http://example.com/a.js"]);
+        assert_eq!(key, None);
+
+        let (urls, key) = parse_cdx_page("\n").unwrap();
+        assert!(urls.is_empty() && key.is_none());
+        assert!(parse_cdx_page("<html>503</html>").is_err());
+    }
+
+    #[test]
+    fn wayback_urls_are_summarised() {
+        let urls = vec![
+            "https://www.example.com/login.php?next=/&user=a".to_string(),
+            "https://www.example.com/login.php?next=/&user=a".to_string(),
+            "http://api.example.com/v1/items?id=1".to_string(),
+            "http://example.com/static/app.JS".to_string(),
+            "http://example.com/dir.name/".to_string(),
+            "not a url".to_string(),
+        ];
+        let record = parse_wayback_urls("example.com", urls);
+
+        assert_eq!(record.urls.len(), 5);
+        assert_eq!(
+            record.hosts.iter().collect::<Vec<_>>(),
+            ["api.example.com", "example.com", "www.example.com"]
+        );
+        assert_eq!(record.parameters.get("next"), Some(&1));
+        assert_eq!(record.parameters.get("id"), Some(&1));
+        assert_eq!(record.extensions.get("php"), Some(&1));
+        assert_eq!(record.extensions.get("js"), Some(&1));
+        assert_eq!(record.extensions.get("name"), None);
+    }
+
+    #[test]
+    fn resume_key_is_not_encoded_twice() {
+        let key = "com%2Cexample%29%2F+20200101000000";
+        let url = cdx_url("example.com", 100, Some(key)).unwrap();
+        assert!(url.as_str().contains("url=*.example.com%2F*"));
+        assert!(url.as_str().ends_with("&resumeKey=com%2Cexample%29%2F+20200101000000"));
+
+        let first = cdx_url("example.com", 100, None).unwrap();
+        assert!(!first.as_str().contains("resumeKey"));
+    }
+}
@@ -3,18 +3,26 @@ use std::net::IpAddr;
 use reqwest::{Client, Error};
 use serde_json::Value;
 use serde_json::json;
-use std::fs::File;
 use std::io::Write;
+use regex::Regex;
+use std::sync::OnceLock;
+use crate::options::SearchOptions;
+use crate::target::Network;
+use crate::read_list::open_output_file;
 
-pub async fn query_zoom_eye(ip: &str) -> Result<String, Error> {
+pub async fn query_zoom_eye(ip: &str, page: usize) -> Result<String, Error> {
+    query_zoom_eye_host_search(&format!("ip:{}", ip), page).await
+}
+
+pub async fn query_zoom_eye_host_search(query: &str, page: usize) -> Result<String, Error> {
     let api_key = get_zoom_eye_api_key().expect("ZOOMEYE_API not found");
 
-    let query = format!("ip:{}", ip);
-    let url = format!("https://api.zoomeye.org/host/search?query={}", query);
+    let url = "https://api.zoomeye.org/host/search";
 
     let client = Client::new();
     let response = client
-        .get(&url)
+        .get(url)
+        .query(&[("query", query.to_string()), ("page", page.to_string())])
         .header("API-KEY", api_key)
         .send()
         .await?;
@@ -24,6 +32,103 @@ pub async fn query_zoom_eye(ip: &str) -> Result<String, Error> {
     Ok(response_body)
 }
 
+pub fn zoom_eye_network_query(network: &Network) -> String {
+    match network {
+        Network::Asn(asn) => format!("asn:{}", asn),
+        Network::Org(org) => format!("org:\"{}\"", org.replace('"', "")),
+    }
+}
+
+// search_type 0 returns associated domains, 1 returns subdomains
+pub async fn query_zoom_eye_domain(domain: &str, search_type: u8, page: usize) -> Result<String, Error> {
+    let api_key = get_zoom_eye_api_key().expect("ZOOMEYE_API not found");
+
+    let url = "https://api.zoomeye.org/domain/search";
+
+    let client = Client::new();
+    let response = client
+        .get(url)
+        .query(&[
+            ("q", domain.to_string()),
+            ("type", search_type.to_string()),
+            ("page", page.to_string()),
+        ])
+        .header("API-KEY", api_key)
+        .send()
+        .await?;
+
+    response.text().await
+}
+
+pub async fn query_zoom_eye_web(query: &str, page: usize) -> Result<String, Error> {
+    let api_key = get_zoom_eye_api_key().expect("ZOOMEYE_API not found");
+
+    let url = "https://api.zoomeye.org/web/search";
+
+    let client = Client::new();
+    let response = client
+        .get(url)
+        .query(&[("query", query.to_string()), ("page", page.to_string())])
+        .header("API-KEY", api_key)
+        .send()
+        .await?;
+
+    response.text().await
+}
+
+// Walks ZoomEye's page parameter until the limit is met or the results run out.
+// The first page is kept as-is so total/available survive, with the list under
+// results_key replaced by everything collected. Without a limit only one page is
+// fetched, since every page costs credits.
+async fn collect_zoom_eye_pages<F, Fut>(
+    results_key: &str,
+    limit: Option<usize>,
+    fetch_page: F,
+) -> Result<Value, Box<dyn std::error::Error>>
+where
+    F: Fn(usize) -> Fut,
+    Fut: std::future::Future<Output = Result<String, Error>>,
+{
+    let mut first_page: Option<Value> = None;
+    let mut collected: Vec<Value> = Vec::new();
+    let mut page = 1;
+
+    loop {
+        let page_json: Value = serde_json::from_str(&fetch_page(page).await?)?;
+        let entries = page_json[results_key].as_array().cloned().unwrap_or_default();
+        let total = page_json["total"].as_u64().unwrap_or(0) as usize;
+        let page_was_empty = entries.is_empty();
+
+        collected.extend(entries);
+        if first_page.is_none() {
+            first_page = Some(page_json);
+        }
+
+        let wanted = match limit {
+            Some(limit) => limit,
+            None => break,
+        };
+        if page_was_empty || collected.len() >= wanted || collected.len() >= total {
+            break;
+        }
+        page += 1;
+    }
+
+    if let Some(limit) = limit {
+        collected.truncate(limit);
+    }
+
+    let mut result = first_page.unwrap_or_else(|| json!({}));
+    result[results_key] = Value::Array(collected);
+    Ok(result)
+}
+
+pub fn is_domain(target: &str) -> bool {
+    let domain_regex =
+        Regex::new(r"^(?:[a-zA-Z0-9](?:[a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?\.)+[a-zA-Z]{2,}$").unwrap();
+    domain_regex.is_match(target)
+}
+
 /*pub fn is_ip(target: &str) -> bool {
     target.parse::<IpAddr>().is_ok()
 }*/
@@ -35,54 +140,125 @@ pub fn get_zoom_eye_api_key() -> Option<String> {
 pub async fn run_single_search_zoomeye(
     target: &str,
     output_file: Option<&str>,
+    options: &SearchOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let zoomeye_result = query_zoom_eye(target).await?;
+    if is_domain(target) {
+        return run_domain_search_zoomeye(target, output_file, options).await;
+    }
 
-    let json: Value = serde_json::from_str(&zoomeye_result)?;
+    // An empty "matches" is still written out with its total/available metadata
+    let mut json = collect_zoom_eye_pages("matches", options.limit, |page| query_zoom_eye(target, page)).await?;
+    localize_fields(&mut json, &options.lang);
 
-    if let Some(hits) = json["matches"].as_array() {
-        let filtered_hits: Vec<serde_json::Map<String, Value>> = hits
-            .iter()
-            .filter_map(|hit| match hit {
-                Value::Object(map) => {
-                    if !contains_chinese(map) {
-                        Some(map.clone())
-                    } else {
-                        None
-                    }
-                }
-                _ => None,
-            })
-            .collect();
+    parsed_result(
+        "ZoomEye",
+        &serde_json::to_string_pretty(&json)?,
+        output_file,
+    )
+}
 
-        let filtered_json = json!({
-            "matches": filtered_hits,
-        });
+pub async fn run_domain_search_zoomeye(
+    domain: &str,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut associated = collect_zoom_eye_pages("list", options.limit, |page| query_zoom_eye_domain(domain, 0, page)).await?;
+    let mut subdomains = collect_zoom_eye_pages("list", options.limit, |page| query_zoom_eye_domain(domain, 1, page)).await?;
+    localize_fields(&mut associated, &options.lang);
+    localize_fields(&mut subdomains, &options.lang);
 
-        parsed_result(
-            "ZoomEye",
-            &serde_json::to_string_pretty(&filtered_json)?,
-            output_file,
-        )?;
-    } else {
-        println!("ZoomEye:\nNo results found");
-    }
+    let domain_json = json!({
+        "associated_domains": associated,
+        "subdomains": subdomains,
+    });
 
-    Ok(())
+    parsed_result(
+        "ZoomEye",
+        &serde_json::to_string_pretty(&domain_json)?,
+        output_file,
+    )
+}
+
+// Site queries such as "site:example.com" go through the web search
+pub async fn run_query_search_zoomeye(
+    query: &str,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut web_json = collect_zoom_eye_pages("matches", options.limit, |page| query_zoom_eye_web(query, page)).await?;
+    localize_fields(&mut web_json, &options.lang);
+
+    parsed_result(
+        "ZoomEye",
+        &serde_json::to_string_pretty(&web_json)?,
+        output_file,
+    )
+}
+
+pub async fn run_network_search_zoomeye(
+    network: &Network,
+    output_file: Option<&str>,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let query = zoom_eye_network_query(network);
+    let mut host_json = collect_zoom_eye_pages("matches", options.limit, |page| query_zoom_eye_host_search(&query, page)).await?;
+    localize_fields(&mut host_json, &options.lang);
+
+    parsed_result(
+        "ZoomEye",
+        &serde_json::to_string_pretty(&host_json)?,
+        output_file,
+    )
 }
 
-fn contains_chinese(hit: &serde_json::Map<String, Value>) -> bool {
-    if let Some(zh_cn) = hit.get("zh-CN") {
-        if let Some(text) = zh_cn.as_str() {
-            !text.is_empty()
-        } else {
-            true
+// ZoomEye geo fields carry one string per locale, e.g.
+// {"names": {"en": "Mountain View", "zh-CN": "..."}}. Every such map in the
+// response is projected down to a single string in the requested language,
+// falling back to English and then to whichever translation is present.
+// Hits are never dropped and the pagination metadata is left untouched.
+pub fn localize_fields(value: &mut Value, lang: &str) {
+    match value {
+        Value::Object(map) => {
+            if is_locale_map(map) {
+                *value = pick_locale(map, lang);
+            } else {
+                for field in map.values_mut() {
+                    localize_fields(field, lang);
+                }
+            }
         }
-    } else {
-        false
+        Value::Array(items) => {
+            for item in items {
+                localize_fields(item, lang);
+            }
+        }
+        _ => {}
     }
 }
 
+fn is_locale_map(map: &serde_json::Map<String, Value>) -> bool {
+    static LOCALE_KEY: OnceLock<Regex> = OnceLock::new();
+    let locale_key = LOCALE_KEY.get_or_init(|| Regex::new(r"^[a-z]{2}(-[A-Za-z]{2,4})?$").unwrap());
+    !map.is_empty()
+        && map.contains_key("en")
+        && map
+            .iter()
+            .all(|(key, val)| locale_key.is_match(key) && (val.is_string() || val.is_null()))
+}
+
+fn pick_locale(map: &serde_json::Map<String, Value>, lang: &str) -> Value {
+    let non_empty = |key: &str| {
+        map.get(key)
+            .filter(|val| val.as_str().is_some_and(|text| !text.is_empty()))
+            .cloned()
+    };
+
+    non_empty(lang)
+        .or_else(|| non_empty("en"))
+        .or_else(|| map.values().find(|val| val.as_str().is_some_and(|text| !text.is_empty())).cloned())
+        .unwrap_or(Value::Null)
+}
+
 fn parsed_result(
     search_engine: &str,
     result: &str,
@@ -90,7 +266,7 @@ fn parsed_result(
 ) -> Result<(), Box<dyn std::error::Error>> {
     match output_file {
         Some(file) => {
-            let mut file = File::create(file)?;
+            let mut file = open_output_file(file)?;
             writeln!(file, "{}:\n{}", search_engine, result)?;
         }
         None => {
@@ -99,3 +275,47 @@ fn parsed_result(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locale_maps_resolve_to_the_requested_language() {
+        let mut json = json!({
+            "total": 2,
+            "available": 2,
+            "matches": [
+                {"geoinfo": {"city": {"names": {"en": "Mountain View", "zh-CN": "山景城"}}}},
+                {"geoinfo": {"city": {"names": {"en": "", "zh-CN": "北京"}}, "asn": 15169}}
+            ]
+        });
+        localize_fields(&mut json, "zh-CN");
+        assert_eq!(json["matches"][0]["geoinfo"]["city"]["names"], "山景城");
+
+        let mut json_en = json!({"matches": [{"names": {"en": "", "zh-CN": "北京"}}], "total": 1});
+        localize_fields(&mut json_en, "en");
+        // Empty English names fall back to whichever translation exists
+        assert_eq!(json_en["matches"][0]["names"], "北京");
+        assert_eq!(json_en["total"], 1);
+    }
+
+    #[test]
+    fn hits_and_non_locale_objects_are_kept() {
+        let mut json = json!({
+            "total": 2,
+            "matches": [
+                {"portinfo": {"service": "http", "port": 80}},
+                {"names": {"en": "Paris", "fr": "Paris"}, "ip": "192.0.2.1"}
+            ]
+        });
+        localize_fields(&mut json, "fr");
+        assert_eq!(json["matches"].as_array().unwrap().len(), 2);
+        assert_eq!(json["matches"][0]["portinfo"]["port"], 80);
+        assert_eq!(json["matches"][1]["names"], "Paris");
+        // Maps without an "en" key, or with non-locale keys, are not locale maps
+        let mut other = json!({"fr": "a", "de": "b"});
+        localize_fields(&mut other, "de");
+        assert_eq!(other, json!({"fr": "a", "de": "b"}));
+    }
+}